@@ -19,9 +19,10 @@
 //! Here are a few points from the above example:
 //! 1. You can reference types defined outside of `co
 //! 2. You can reference types defined inside of `com
-//! invocation.
+//!    invocation.
 //! 3. You can expose types outside of the current mo
-//! the `pub` keyword.
+//!    the `pub` keyword.
+//!
 //! You can find the full feature set below!
 //! ## Type Alias
 //! You can rename types within the scope of the macr
@@ -39,6 +40,26 @@
 //!    field: None
 //! };
 //! ```
+//! Declared as `struct` or `enum`, the alias is a copy: a new type with the same fields, which
+//! can't be used in place of the original. Add `#[compose(from)]` to convert between the two with
+//! `From` and `Into`. Declared as `type`, the alias is a Rust type alias, so both names refer to
+//! the same type. Operations can still be applied to either name.
+//! ```rust
+//! # use composite_types::{compose_type};
+//! compose_type! {
+//!     pub struct BankAccount {
+//!         pub balance: u64
+//!     }
+//!     #[compose(from)]
+//!     pub struct Savings = BankAccount;
+//!     pub type Account = BankAccount;
+//!     pub struct AccountPatch = Optional(Account);
+//! }
+//! let savings = Savings::from(BankAccount { balance: 10 });
+//! let account: Account = savings.into();
+//! let patch = AccountPatch { balance: Some(account.balance) };
+//! assert_eq!(patch.balance, Some(10));
+//! ```
 //! ## Type Functions
 //! ### Optional
 //! You can wrap a type in `Option` by using the `Opt
@@ -55,6 +76,26 @@
 //!   field: None
 //! };
 //! ```
+//! Fields of any sized type are wrapped, including references, tuples, arrays and function
+//! pointers. Unsized fields such as `[u8]` or `dyn Trait` are an error.
+//! ```rust
+//! # use composite_types::{compose_type};
+//! compose_type! {
+//!    struct Request<'a> {
+//!       path: &'a str,
+//!       range: (u64, u64),
+//!       digest: [u8; 4],
+//!       on_done: fn(u16)
+//!    }
+//!    struct RequestPatch = Optional(Request);
+//! }
+//! const PATCH: RequestPatch<'static> = RequestPatch {
+//!   path: Some("/"),
+//!   range: None,
+//!   digest: Some([0; 4]),
+//!   on_done: None
+//! };
+//! ```
 //! ### Required
 //! You can unwrap a type from `Option` by using the
 //! ```rust
@@ -70,6 +111,485 @@
 //!    field: FieldType {}
 //! };
 //! ```
+//! Both `Optional` and `Required` can be restricted to the fields listed after the type, leaving
+//! every other field unchanged. For enums, listing a variant selects all of its fields.
+//! ```rust
+//! # use composite_types::{compose_type};
+//! compose_type! {
+//!   struct User {
+//!      id: u64,
+//!      email: String,
+//!      phone: String
+//!   }
+//!   struct UserPatch = Optional(User, email, phone);
+//!   struct UserUpdate = Required(UserPatch, email);
+//! }
+//! const PATCH: UserPatch = UserPatch {
+//!    id: 0,
+//!    email: None,
+//!    phone: None
+//! };
+//! const UPDATE: UserUpdate = UserUpdate {
+//!    id: 0,
+//!    email: String::new(),
+//!    phone: None
+//! };
+//! ```
+//! `Option` is recognized as `Option`, `std::option::Option` or `core::option::Option`. Other
+//! generic types can be treated like it with `#![compose(nullable(...))]` before the items of the
+//! macro. The `type: T` selector matches fields of these wrappers around `T` as well.
+//! ```rust
+//! # use composite_types::{compose_type};
+//! type Maybe<T> = Option<T>;
+//! compose_type! {
+//!   #![compose(nullable(Maybe))]
+//!   struct Profile {
+//!      nickname: Maybe<String>,
+//!      bio: std::option::Option<String>
+//!   }
+//!   struct CompleteProfile = Required(Profile);
+//! }
+//! const PROFILE: CompleteProfile = CompleteProfile {
+//!    nickname: String::new(),
+//!    bio: String::new()
+//! };
+//! ```
+//! ### Deep Optional and Required
+//! `DeepOptional` and `DeepRequired` also apply to the types a type contains which are defined in
+//! the same macro, including through `Vec`, `Box` and the values of `HashMap`. A type is generated
+//! for each of them, named after it, e.g. `AddressOptional` for `Address`. Types which contain
+//! themselves refer back to the generated type.
+//! ```rust
+//! # use composite_types::{compose_type};
+//! compose_type! {
+//!   struct Address {
+//!      city: String
+//!   }
+//!   struct User {
+//!      name: String,
+//!      address: Address,
+//!      friends: Vec<User>
+//!   }
+//!   struct UserPatch = DeepOptional(User);
+//! }
+//! const PATCH: UserPatch = UserPatch {
+//!    name: None,
+//!    address: Some(AddressOptional { city: None }),
+//!    friends: None
+//! };
+//! ```
+//! ### Wrap and Unwrap
+//! `Wrap(Type, Wrapper)` wraps fields in any generic type, such as `Arc`, `Box` or `Vec`, and
+//! `Unwrap(Type, Wrapper)` takes fields wrapped in it back out. The wrapper can also be given as a
+//! path, e.g. `std::sync::Arc`. Fields which are not wrapped in it are left unchanged by `Unwrap`.
+//! Like `Optional`, both can be restricted to some fields.
+//! ```rust
+//! # use composite_types::{compose_type};
+//! use std::sync::Arc;
+//! compose_type! {
+//!   struct Config {
+//!      name: String,
+//!      retries: u8
+//!   }
+//!   struct SharedConfig = Wrap(Config, std::sync::Arc, name);
+//!   struct Columns = Wrap(Config, Vec);
+//!   struct OwnedConfig = Unwrap(SharedConfig, Arc);
+//! }
+//! let shared = SharedConfig { name: Arc::new(String::from("primary")), retries: 3 };
+//! let columns = Columns { name: vec![String::from("primary")], retries: vec![3] };
+//! let owned = OwnedConfig { name: String::from("primary"), retries: 3 };
+//! ```
+//! ### Map
+//! `Map(Type, pattern => replacement, ...)` rewrites the types of fields by pattern. `_` matches
+//! any type and metavariables such as `$T` match any type and can be used in the replacement,
+//! which can only use the metavariables its pattern binds. At each type, the first rule which
+//! matches replaces it, and types without a match are searched for matches inside them. Lifetimes
+//! introduced by replacements are declared on the new type.
+//! ```rust
+//! # use composite_types::{compose_type};
+//! use std::borrow::Cow;
+//! compose_type! {
+//!   struct Profile {
+//!      name: String,
+//!      bio: Option<String>,
+//!      links: Vec<String>
+//!   }
+//!   struct ProfileView = Map(Profile, Option<$T> => Vec<$T>, String => Cow<'a, str>);
+//! }
+//! let view: ProfileView<'static> = ProfileView {
+//!    name: Cow::Borrowed("Ada"),
+//!    bio: vec![],
+//!    links: vec![Cow::Borrowed("https://example.com")]
+//! };
+//! ```
+//! ### Pick
+//! You can keep only a subset of a type's fields by using `Pick`. Attributes and visibility of
+//! the picked fields are kept.
+//! ```rust
+//! # use composite_types::{compose_type};
+//! compose_type! {
+//!   struct User {
+//!      id: u64,
+//!      name: String,
+//!      email: String
+//!   }
+//!   struct UserSummary = Pick(User, id, name);
+//! }
+//! const EXAMPLE: UserSummary = UserSummary {
+//!    id: 0,
+//!    name: String::new()
+//! };
+//! ```
+//! ### Omit
+//! You can drop a subset of a type's fields by using `Omit`. Every listed field must exist on the
+//! type.
+//! ```rust
+//! # use composite_types::{compose_type};
+//! compose_type! {
+//!   struct User {
+//!      id: u64,
+//!      name: String,
+//!      created_at: u64
+//!   }
+//!   struct NewUser = Omit(User, id, created_at);
+//! }
+//! const EXAMPLE: NewUser = NewUser {
+//!    name: String::new()
+//! };
+//! ```
+//! ### Field Selectors
+//! `Pick`, `Omit`, `Optional`, `Required`, `Wrap` and `Unwrap` share one syntax for choosing
+//! fields. Besides exact names, fields can be selected by a glob over their names (`addr_*`), by
+//! their type (`type: Option<_>`, where `_` matches any type), or by an attribute they carry
+//! (`#[serde]`). `!selector` excludes fields, and on its own selects every other field. Selectors
+//! which match nothing are reported as errors.
+//! ```rust
+//! # use composite_types::{compose_type};
+//! compose_type! {
+//!   struct User {
+//!      id: u64,
+//!      addr_line1: String,
+//!      addr_city: String,
+//!      nickname: Option<String>
+//!   }
+//!   struct Address = Pick(User, addr_*);
+//!   struct UserPatch = Optional(User, !id);
+//!   struct Contact = Omit(User, type: Option<_>, addr_*, !addr_city);
+//! }
+//! const ADDRESS: Address = Address {
+//!    addr_line1: String::new(),
+//!    addr_city: String::new()
+//! };
+//! const PATCH: UserPatch = UserPatch {
+//!    id: 0,
+//!    addr_line1: None,
+//!    addr_city: None,
+//!    nickname: None
+//! };
+//! const CONTACT: Contact = Contact {
+//!    id: 0,
+//!    addr_city: String::new()
+//! };
+//! ```
+//! ### Merge
+//! You can flatten the fields of several types into one type by using `Merge`, or the shorthand
+//! `A & B`. A field defined by more than one of the merged types is an error unless a conflict
+//! policy is given:
+//! - `conflict = first`: the field of the earliest listed type wins, and the others are dropped.
+//! - `conflict = last`: the field of the latest listed type wins, at the position of the first.
+//!
+//! The losing fields are dropped without a warning, along with their types, so below
+//! `Account::updated_at` is an `Option<u64>` and the `u64` of `BankAccount` is gone.
+//! ```rust
+//! # use composite_types::{compose_type};
+//! compose_type! {
+//!   struct BankAccount {
+//!      balance: i32,
+//!      updated_at: u64
+//!   }
+//!   struct Audit {
+//!      created_at: u64,
+//!      updated_at: Option<u64>
+//!   }
+//!   struct Owner {
+//!      owner: String
+//!   }
+//!   struct SavingsAccount = BankAccount & Owner;
+//!   struct Account = Merge(BankAccount, Audit, Owner, conflict = last);
+//! }
+//! const EXAMPLE: Account = Account {
+//!    balance: 0,
+//!    updated_at: None,
+//!    created_at: 0,
+//!    owner: String::new()
+//! };
+//! ```
+//! ## Nested Operations
+//! Operations can be used as the parameters of other operations. Only the outermost type is
+//! generated, so intermediate steps don't need their own names.
+//! ```rust
+//! # use composite_types::{compose_type};
+//! compose_type! {
+//!   struct User {
+//!      id: u64,
+//!      name: String,
+//!      email: Option<String>
+//!   }
+//!   struct UserPatch = Optional(Omit(User, id));
+//!   struct Contact = Required(Pick(User, name, email)) & Pick(User, id);
+//! }
+//! const EXAMPLE: UserPatch = UserPatch {
+//!    name: None,
+//!    email: None
+//! };
+//! ```
+//! ## Tuple and Unit Structs
+//! Operations keep the kind of the struct they are applied to, so tuple structs stay tuple structs
+//! and unit structs stay unit structs. Merging tuple structs concatenates their fields, and `Pick`
+//! and `Omit` select them by type or attribute, keeping their order.
+//! ```rust
+//! # use composite_types::{compose_type};
+//! compose_type! {
+//!   struct Meters(Option<f64>);
+//!   struct Marker;
+//!   struct RequiredMeters = Required(Meters);
+//!   struct OptionalMarker = Optional(Marker);
+//!   struct Segment = Meters & RequiredMeters;
+//!   struct Pair(u8, String, u8);
+//!   struct Bytes = Pick(Pair, type: u8);
+//! }
+//! const EXAMPLE: Segment = Segment(None, 1.0);
+//! const MARKER: OptionalMarker = OptionalMarker;
+//! const BYTES: Bytes = Bytes(1, 2);
+//! ```
+//! ## Generics
+//! Generic parameters, bounds and where clauses are carried over to the generated types. Parameters
+//! which are no longer used by the remaining fields are dropped. `type: T` restricts `Optional` and
+//! `Required` to the fields whose type is the generic parameter `T`.
+//! ```rust
+//! # use composite_types::{compose_type};
+//! # use std::fmt::Debug;
+//! compose_type! {
+//!   struct Page<'a, T: Clone> where T: Debug {
+//!      items: &'a [T],
+//!      current: T,
+//!      total: usize
+//!   }
+//!   struct PageDraft = Optional(Page, type: T);
+//!   struct PageCount = Pick(Page, total);
+//! }
+//! const EXAMPLE: PageDraft<'static, u8> = PageDraft {
+//!    items: &[],
+//!    current: None,
+//!    total: 0
+//! };
+//! const COUNT: PageCount = PageCount { total: 0 };
+//! ```
+//! Generic types can also be instantiated with concrete arguments, which substitutes them through
+//! every field and produces a type without generic parameters.
+//! ```rust
+//! # use composite_types::{compose_type};
+//! compose_type! {
+//!   struct User {
+//!      id: u64
+//!   }
+//!   struct Page<T> {
+//!      items: Vec<T>,
+//!      current: T
+//!   }
+//!   struct UserPage = Page<User>;
+//!   struct UserPageDraft = Optional(Page<User>);
+//! }
+//! const EXAMPLE: UserPageDraft = UserPageDraft {
+//!    items: None,
+//!    current: None
+//! };
+//! ```
+//! ## Enums
+//! Enums can be composed as well, and are declared with `enum` instead of `struct`. `Optional`
+//! and `Required` apply to the fields of every variant, while `Pick`, `Omit` and `Merge` select
+//! and combine variants. Discriminants, attributes and the kind of each variant are preserved.
+//! ```rust
+//! # use composite_types::{compose_type};
+//! compose_type! {
+//!   enum State {
+//!      Loading,
+//!      Loaded { items: Vec<String>, total: Option<u64> },
+//!      Failed(Option<String>)
+//!   }
+//!   enum StatePatch = Optional(State);
+//!   enum Settled = Required(Omit(State, Loading));
+//! }
+//! const PATCH: StatePatch = StatePatch::Failed(None);
+//! const SETTLED: Settled = Settled::Failed(String::new());
+//! ```
+//! ## Unions
+//! `enum Shape = Circle | Square;` generates an enum with one tuple variant per type, along with
+//! `From` conversions into the enum and `TryFrom` conversions back to each type. A failed
+//! `TryFrom` conversion returns the enum unchanged. Attributes other than `#[compose(...)]` on a
+//! composition, such as `#[derive(Debug)]`, are added to the type it generates.
+//! ```rust
+//! # use composite_types::{compose_type};
+//! compose_type! {
+//!   #[derive(Debug, PartialEq)]
+//!   struct Circle { radius: f64 }
+//!   #[derive(Debug, PartialEq)]
+//!   struct Square(f64);
+//!   #[derive(Debug)]
+//!   pub enum Shape = Circle | Square;
+//! }
+//! let shape = Shape::from(Circle { radius: 1.0 });
+//! assert!(matches!(shape, Shape::Circle(_)));
+//! assert_eq!(Circle::try_from(shape).unwrap(), Circle { radius: 1.0 });
+//! assert!(Square::try_from(Shape::from(Circle { radius: 2.0 })).is_err());
+//! ```
+//! ## Field Names
+//! `enum UserField = KeyOf(User);` generates a fieldless enum with one variant per field of
+//! `User`. It implements `Display` and `FromStr` with the field names as declared, and lists
+//! every variant in its `ALL` constant.
+//! ```rust
+//! # use composite_types::{compose_type};
+//! compose_type! {
+//!   struct User {
+//!      id: u64,
+//!      created_at: u64
+//!   }
+//!   pub enum UserField = KeyOf(User);
+//! }
+//! assert_eq!(UserField::ALL, [UserField::Id, UserField::CreatedAt]);
+//! assert_eq!(UserField::CreatedAt.to_string(), "created_at");
+//! assert_eq!("id".parse::<UserField>(), Ok(UserField::Id));
+//! assert!("email".parse::<UserField>().is_err());
+//! ```
+//! ## Renaming Fields
+//! `Rename` produces a copy of a struct with differently named fields. `old => new` renames a
+//! single field, while naming conventions (`camelCase`, `PascalCase`, `snake_case`,
+//! `SCREAMING_SNAKE_CASE`), `prefix = "..."` and `suffix = "..."` apply to every other field in
+//! the order they are listed. `From` conversions between the original and the renamed struct are
+//! generated in both directions. Structs renamed to `camelCase`, `PascalCase` or
+//! `SCREAMING_SNAKE_CASE` are marked `#[allow(non_snake_case)]`.
+//! ```rust
+//! # use composite_types::{compose_type};
+//! compose_type! {
+//!   #[derive(Debug, PartialEq)]
+//!   struct User {
+//!      name: String,
+//!      created_at: u64
+//!   }
+//!   struct UserRecord = Rename(User, name => display_name, camelCase);
+//! }
+//! let record = UserRecord::from(User { name: String::from("Ada"), created_at: 1 });
+//! assert_eq!(record.display_name, "Ada");
+//! assert_eq!(record.createdAt, 1);
+//! assert_eq!(User::from(record), User { name: String::from("Ada"), created_at: 1 });
+//! ```
+//! ## Field Types
+//! `type UserId = FieldType(User, id);` declares a type alias for the type of the `id` field of
+//! `User`, so signatures can follow the field when its type changes.
+//! ```rust
+//! # use composite_types::{compose_type};
+//! compose_type! {
+//!   struct User {
+//!      id: u64,
+//!      name: String
+//!   }
+//!   type UserId = FieldType(User, id);
+//! }
+//! fn find(users: &[User], id: UserId) -> Option<&User> {
+//!     users.iter().find(|user| user.id == id)
+//! }
+//! let users = vec![User { id: 7, name: String::from("Ada") }];
+//! assert_eq!(find(&users, 7).map(|user| user.name.as_str()), Some("Ada"));
+//! ```
+//! ## Modules
+//! Types can be grouped into `mod` blocks, which are emitted as modules. Operations refer to types
+//! in other modules by path, relative to the module they are declared in, e.g. `models::User`
+//! from the root of the macro or `super::User` from a nested module.
+//! ```rust
+//! # use composite_types::{compose_type};
+//! compose_type! {
+//!   pub mod models {
+//!     pub struct User {
+//!        pub id: u64,
+//!        pub name: String
+//!     }
+//!     pub mod admin {
+//!       pub struct Admin = Pick(super::User, id);
+//!     }
+//!   }
+//!   pub struct UserPatch = Optional(models::User);
+//! }
+//! let patch = UserPatch { id: None, name: Some(String::from("Ada")) };
+//! let admin = models::admin::Admin { id: 1 };
+//! assert_eq!(patch.name.as_deref(), Some("Ada"));
+//! assert_eq!(admin.id, 1);
+//! ```
+//! ## Other Items
+//! Any other item, e.g. `use` declarations, `impl` blocks, functions, constants and type aliases
+//! like `type Users = Vec<User>;`, is emitted unchanged. The output keeps the order of the
+//! declarations, so implementations can be written next to the types they belong to.
+//! ```rust
+//! # use composite_types::{compose_type};
+//! compose_type! {
+//!   struct User {
+//!      id: u64,
+//!      name: String
+//!   }
+//!   struct UserPatch = Optional(User);
+//!   impl UserPatch {
+//!     fn is_empty(&self) -> bool {
+//!       self.id.is_none() && self.name.is_none()
+//!     }
+//!   }
+//!   type Users = Vec<User>;
+//! }
+//! let users: Users = vec![User { id: 1, name: String::from("Ada") }];
+//! assert_eq!(users[0].name, "Ada");
+//! assert!(UserPatch { id: None, name: None }.is_empty());
+//! ```
+//! ## Declaration Order
+//! Types can be used before they are declared, so they can be grouped however reads best. Each
+//! composition is evaluated after the types it is composed from, while the output keeps the order
+//! of the declarations. A type which is composed from itself, directly or through other types, is
+//! reported along with the cycle, e.g. ``Cyclic composition: `A` -> `B` -> `A` ``.
+//! ```rust
+//! # use composite_types::{compose_type};
+//! compose_type! {
+//!   pub struct AccountPatch = Optional(Account);
+//!   pub struct Account = BankAccount & Owner;
+//!   pub struct BankAccount {
+//!      pub balance: u64
+//!   }
+//!   pub struct Owner {
+//!      pub name: String
+//!   }
+//! }
+//! let patch = AccountPatch { balance: Some(10), name: None };
+//! assert_eq!(patch.balance, Some(10));
+//! ```
+//! ## Internal Types
+//! Types declared with `#[compose(internal)]` can be used by operations, but aren't emitted. This
+//! keeps types which only exist to derive other types out of the output. Emitted types can't name
+//! internal types in their fields, and conversions generated between them, e.g. by `Rename`, are
+//! left out.
+//! ```rust
+//! # use composite_types::{compose_type};
+//! compose_type! {
+//!   #[compose(internal)]
+//!   struct Timestamps {
+//!      created_at: u64,
+//!      updated_at: u64
+//!   }
+//!   #[compose(internal)]
+//!   struct Named {
+//!      name: String
+//!   }
+//!   pub struct User = Named & Timestamps;
+//!   pub struct UserPatch = Optional(User);
+//! }
+//! let user = User { name: String::from("Ada"), created_at: 1, updated_at: 2 };
+//! assert_eq!(user.updated_at, 2);
+//! ```
 //!
 mod parser;
 mod processor;
@@ -21,7 +21,6 @@ fn composite_type_processing_pipeline(input: proc_macro2::TokenStream)
 mod tests {
     use quote::quote;
     use super::*;
-    use syn::parse_quote;
 
     #[test]
     fn test_composite_type() {
@@ -49,7 +48,547 @@ mod tests {
             }
         };
 
-        let actual = composite_type_impl(input.into());
+        let actual = composite_type_impl(input);
         assert_eq!(actual.to_string(), expected.to_string());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_composite_type_nested() {
+        let input = quote! {
+            struct User {
+                id: u64,
+                name: String,
+                email: String
+            }
+            struct UserPatch = Optional(Omit(User, id));
+        };
+
+        let expected = quote! {
+            struct User {
+                id: u64,
+                name: String,
+                email: String
+            }
+            struct UserPatch {
+                name: Option<String>,
+                email: Option<String>
+            }
+        };
+
+        let actual = composite_type_impl(input);
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_composite_type_enum() {
+        let input = quote! {
+            enum State {
+                Loading = 1,
+                Loaded { items: Vec<String>, total: Option<u64> },
+                Failed(Option<String>)
+            }
+            pub enum StatePatch = Optional(State);
+            enum Settled = Omit(Required(State), Loading);
+        };
+
+        let expected = quote! {
+            enum State {
+                Loading = 1,
+                Loaded { items: Vec<String>, total: Option<u64> },
+                Failed(Option<String>)
+            }
+            pub enum StatePatch {
+                Loading = 1,
+                Loaded { items: Option<Vec<String> >, total: Option<u64> },
+                Failed(Option<String>)
+            }
+            enum Settled {
+                Loaded { items: Vec<String>, total: u64 },
+                Failed(String)
+            }
+        };
+
+        let actual = composite_type_impl(input);
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_composite_type_attributes() {
+        let input = quote! {
+            #[derive(Debug)]
+            struct User {
+                id: u64
+            }
+            #[derive(Clone)]
+            #[compose(from)]
+            struct Account = User;
+            #[derive(Clone)]
+            struct UserPatch = Optional(User);
+            #[derive(Debug)]
+            enum Member = User | Account;
+        };
+
+        let actual = composite_type_impl(input).to_string();
+        assert!(actual.contains(&quote! {
+            #[derive(Debug)]
+            #[derive(Clone)]
+            struct Account {
+                id: u64
+            }
+        }.to_string()));
+        assert!(actual.contains(&quote! {
+            #[derive(Debug)]
+            #[derive(Clone)]
+            struct UserPatch {
+                id: Option<u64>
+            }
+        }.to_string()));
+        assert!(actual.contains(&quote! {
+            #[derive(Debug)]
+            enum Member {
+                User(User),
+                Account(Account)
+            }
+        }.to_string()));
+    }
+
+    #[test]
+    fn test_composite_type_kind_mismatch() {
+        let input = quote! {
+            enum State {
+                Loading
+            }
+            struct StatePatch = Optional(State);
+        };
+
+        let actual = composite_type_processing_pipeline(input).err().unwrap();
+        assert_eq!(actual.to_string(),
+                   "`StatePatch` is declared as a struct, but its composition produces an enum. Declare it as `enum StatePatch = ...` instead.");
+    }
+
+    #[test]
+    fn test_composite_type_rename() {
+        let input = quote! {
+            struct User {
+                name: String
+            }
+            struct UserRecord = Rename(User, prefix = "user_");
+        };
+
+        let expected = quote! {
+            struct User {
+                name: String
+            }
+            struct UserRecord {
+                user_name: String
+            }
+            impl ::core::convert::From<User> for UserRecord {
+                fn from(value: User) -> Self {
+                    UserRecord {
+                        user_name: value.name
+                    }
+                }
+            }
+            impl ::core::convert::From<UserRecord> for User {
+                fn from(value: UserRecord) -> Self {
+                    User {
+                        name: value.user_name
+                    }
+                }
+            }
+        };
+
+        let actual = composite_type_impl(input);
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_composite_type_field_type() {
+        let input = quote! {
+            struct User {
+                id: u64,
+                name: String
+            }
+            pub type UserId = FieldType(User, id);
+        };
+
+        let expected = quote! {
+            struct User {
+                id: u64,
+                name: String
+            }
+            pub type UserId = u64;
+        };
+
+        let actual = composite_type_impl(input);
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_composite_type_deep_optional() {
+        let input = quote! {
+            struct Address {
+                city: String
+            }
+            struct User {
+                address: Box<Address>
+            }
+            pub struct UserPatch = DeepOptional(User);
+        };
+
+        let expected = quote! {
+            struct Address {
+                city: String
+            }
+            struct User {
+                address: Box<Address>
+            }
+            struct AddressOptional {
+                city: Option<String>
+            }
+            pub struct UserPatch {
+                address: Option<Box<AddressOptional> >
+            }
+        };
+
+        let actual = composite_type_impl(input);
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_composite_type_module_paths() {
+        let input = quote! {
+            pub mod models {
+                pub struct User {
+                    pub id: u64
+                }
+                pub mod admin {
+                    pub struct Admin = super::User;
+                }
+            }
+            struct UserPatch = Optional(models::User);
+            struct AdminPatch = Optional(models::admin::Admin);
+        };
+
+        let expected = quote! {
+            pub mod models {
+                pub struct User {
+                    pub id: u64
+                }
+                pub mod admin {
+                    pub struct Admin {
+                        pub id: u64
+                    }
+                }
+            }
+            pub struct UserPatch {
+                pub id: Option<u64>
+            }
+            pub struct AdminPatch {
+                pub id: Option<u64>
+            }
+        };
+
+        let actual = composite_type_impl(input);
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_composite_type_unresolved_path() {
+        let input = quote! {
+            mod models {
+                struct User {
+                    id: u64
+                }
+            }
+            struct UserPatch = Optional(User);
+            struct AdminPatch = Optional(models::Admin);
+        };
+
+        let actual = composite_type_processing_pipeline(input).err().unwrap();
+        assert!(actual.to_string().starts_with("Type `User` does not exist in the scope of the macro."));
+
+        let input = quote! {
+            mod models {}
+            struct AdminPatch = Optional(models::Admin);
+        };
+
+        let actual = composite_type_processing_pipeline(input).err().unwrap();
+        assert_eq!(actual.to_string(), "Type `models::Admin` does not exist in the scope of the macro");
+    }
+
+    #[test]
+    fn test_composite_type_declaration_order() {
+        let input = quote! {
+            struct User {
+                id: u64
+            }
+            macro_rules! patch {
+                ($name:ident) => { $name { id: None } };
+            }
+            struct Patch = Optional(User);
+            fn empty() -> Patch {
+                patch!(Patch)
+            }
+        };
+
+        let actual = composite_type_impl(input);
+        let kinds = actual.into_iter()
+            .filter_map(|token| match token {
+                proc_macro2::TokenTree::Ident(ident) => Some(ident.to_string()),
+                _ => None
+            })
+            .filter(|ident| ["struct", "macro_rules", "fn"].contains(&ident.as_str()))
+            .collect::<Vec<String>>();
+        assert_eq!(kinds, vec!["struct", "macro_rules", "struct", "fn"]);
+    }
+
+    #[test]
+    fn test_composite_type_items() {
+        let input = quote! {
+            use std::fmt::Debug;
+            struct User {
+                id: u64
+            }
+            struct UserPatch = Optional(User);
+            impl UserPatch {
+                fn is_empty(&self) -> bool {
+                    self.id.is_none()
+                }
+            }
+            type Users = Vec<User>;
+            type Pair = (u8, u8);
+            type Name = &'static str;
+            type Callback = fn(i32) -> i32;
+            type Digest = [u8; 32];
+        };
+
+        let expected = quote! {
+            use std::fmt::Debug;
+            struct User {
+                id: u64
+            }
+            struct UserPatch {
+                id: Option<u64>
+            }
+            impl UserPatch {
+                fn is_empty(&self) -> bool {
+                    self.id.is_none()
+                }
+            }
+            type Users = Vec<User>;
+            type Pair = (u8, u8);
+            type Name = &'static str;
+            type Callback = fn(i32) -> i32;
+            type Digest = [u8; 32];
+        };
+
+        let actual = composite_type_impl(input);
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_composite_type_forward_references() {
+        let input = quote! {
+            pub struct UserPatch = Optional(User);
+            struct User = Pick(models::Account, id);
+            mod models {
+                pub struct Account {
+                    pub id: u64,
+                    pub balance: u64
+                }
+            }
+        };
+
+        let expected = quote! {
+            pub struct UserPatch {
+                pub id: Option<u64>,
+            }
+            pub struct User {
+                pub id: u64,
+            }
+            mod models {
+                pub struct Account {
+                    pub id: u64,
+                    pub balance: u64
+                }
+            }
+        };
+
+        let actual = composite_type_impl(input);
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_composite_type_cycle() {
+        let input = quote! {
+            struct Base {
+                id: u64
+            }
+            struct A = B & Base;
+            struct B = Optional(A);
+        };
+
+        let actual = composite_type_processing_pipeline(input).err().unwrap();
+        assert_eq!(actual.to_string(), "Cyclic composition: `A` -> `B` -> `A`. A type can't be composed from itself");
+    }
+
+    #[test]
+    fn test_composite_type_internal() {
+        let input = quote! {
+            #[compose(internal)]
+            #[derive(Debug)]
+            struct Base {
+                id: u64
+            }
+            #[compose(internal)]
+            struct BasePatch = Optional(Base);
+            pub struct User = Required(BasePatch);
+            struct Record = Rename(Base, prefix = "record_");
+        };
+
+        let expected = quote! {
+            #[derive(Debug)]
+            pub struct User {
+                id: u64
+            }
+            #[derive(Debug)]
+            struct Record {
+                record_id: u64
+            }
+        };
+
+        let actual = composite_type_impl(input);
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_composite_type_transparent_alias() {
+        let input = quote! {
+            struct AccountPatch = Optional(Account);
+            type Account = models::BankAccount;
+            mod models {
+                pub struct BankAccount {
+                    pub balance: u64
+                }
+            }
+            pub type Savings = models::BankAccount;
+            struct SavingsPatch = Optional(Savings);
+            type Bytes = Vec<u8>;
+        };
+
+        let expected = quote! {
+            pub struct AccountPatch {
+                pub balance: Option<u64>
+            }
+            type Account = models::BankAccount;
+            mod models {
+                pub struct BankAccount {
+                    pub balance: u64
+                }
+            }
+            pub type Savings = models::BankAccount;
+            pub struct SavingsPatch {
+                pub balance: Option<u64>
+            }
+            type Bytes = Vec<u8>;
+        };
+
+        let actual = composite_type_impl(input);
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_composite_type_copy_conversions() {
+        let input = quote! {
+            struct BankAccount {
+                balance: u64
+            }
+            #[compose(from)]
+            struct Savings = BankAccount;
+        };
+
+        let expected = quote! {
+            struct BankAccount {
+                balance: u64
+            }
+            struct Savings {
+                balance: u64
+            }
+            impl ::core::convert::From<BankAccount> for Savings {
+                fn from(value: BankAccount) -> Self {
+                    let BankAccount { balance } = value;
+                    Savings { balance }
+                }
+            }
+            impl ::core::convert::From<Savings> for BankAccount {
+                fn from(value: Savings) -> Self {
+                    let Savings { balance } = value;
+                    BankAccount { balance }
+                }
+            }
+        };
+
+        let actual = composite_type_impl(input);
+        assert_eq!(actual.to_string(), expected.to_string());
+
+        let input = quote! {
+            struct BankAccount {
+                balance: u64
+            }
+            #[compose(from)]
+            type Savings = BankAccount;
+        };
+
+        let actual = composite_type_processing_pipeline(input).err().unwrap();
+        assert_eq!(actual.to_string(), "`#[compose(from)]` only applies to copies like `struct Savings = BankAccount;`");
+
+        let input = quote! {
+            #[compose(internal)]
+            struct BankAccount {
+                balance: u64
+            }
+            #[compose(from)]
+            pub struct Savings = BankAccount;
+        };
+
+        let actual = composite_type_processing_pipeline(input).err().unwrap();
+        assert_eq!(
+            actual.to_string(),
+            "`BankAccount` is declared with `#[compose(internal)]` and isn't emitted, so `Savings` can't be converted from and to it");
+    }
+
+    #[test]
+    fn test_composite_type_internal_alias() {
+        let input = quote! {
+            #[compose(internal)]
+            struct Base {
+                id: u64
+            }
+            pub type Alias = Base;
+        };
+
+        let actual = composite_type_processing_pipeline(input).err().unwrap();
+        assert_eq!(
+            actual.to_string(),
+            "`Base` is declared with `#[compose(internal)]` and isn't emitted, so `Alias` can't refer to it");
+    }
+
+    #[test]
+    fn test_composite_type_internal_field() {
+        let input = quote! {
+            #[compose(internal)]
+            struct Base {
+                id: u64
+            }
+            struct User {
+                base: Base
+            }
+        };
+
+        let actual = composite_type_processing_pipeline(input).err().unwrap();
+        assert_eq!(
+            actual.to_string(),
+            "`Base` is declared with `#[compose(internal)]` and isn't emitted, so `User` can't refer to it");
+    }
+}
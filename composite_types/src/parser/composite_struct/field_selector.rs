@@ -0,0 +1,192 @@
+use std::fmt::{Debug, Display, Formatter};
+use proc_macro2::{Ident, Span};
+use quote::ToTokens;
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::{bracketed, Path, Token, Type};
+
+/// Restricts an operator to a subset of the fields of the type it is applied to. For enums, every
+/// selector except `type: T` matches variants rather than their fields.
+pub enum FieldSelector {
+    /// `name` selects the field with that name, or every field of the variant with that name.
+    Name(Ident),
+    /// `addr_*` selects the fields whose name matches the pattern, where `*` matches any text.
+    Glob(String, Span),
+    /// `type: Option<_>` selects the fields whose type matches the pattern, either as declared or
+    /// wrapped in `Option`. `_` matches any type.
+    Type(Type),
+    /// `#[pii]` selects the fields carrying an attribute with that path.
+    Attribute(Path),
+    /// `!id` selects the fields which are not selected by the inner selector.
+    Not(Box<FieldSelector>),
+}
+
+impl Parse for FieldSelector {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Token![!]) {
+            input.parse::<Token![!]>()?;
+            return Ok(FieldSelector::Not(Box::new(input.parse()?)))
+        }
+        if input.peek(Token![type]) {
+            input.parse::<Token![type]>()?;
+            input.parse::<Token![:]>()?;
+            return Ok(FieldSelector::Type(input.parse()?))
+        }
+        if input.peek(Token![#]) {
+            input.parse::<Token![#]>()?;
+            let content;
+            bracketed!(content in input);
+            let path = content.call(Path::parse_mod_style)?;
+            if !content.is_empty() {
+                return Err(content.error("Expected only the path of an attribute, e.g. `#[pii]`"));
+            }
+            return Ok(FieldSelector::Attribute(path))
+        }
+        parse_name_or_glob(input)
+    }
+}
+
+/// Names and globs are written as identifiers separated by `*`, e.g. `addr_*` or `*_at`. Since
+/// `*` is its own token, the pattern is reassembled from the tokens.
+fn parse_name_or_glob(input: ParseStream) -> syn::Result<FieldSelector> {
+    let span = input.span();
+    let mut pattern = String::new();
+    let mut name: Option<Ident> = None;
+    loop {
+        if input.peek(Token![*]) {
+            input.parse::<Token![*]>()?;
+            pattern.push('*');
+            if !input.peek(syn::Ident::peek_any) {
+                break
+            }
+        } else {
+            let ident = input.call(Ident::parse_any)?;
+            pattern.push_str(&ident.unraw().to_string());
+            name = Some(ident);
+            if !input.peek(Token![*]) {
+                break
+            }
+        }
+    }
+    match name {
+        Some(name) if !pattern.contains('*') => Ok(FieldSelector::Name(name)),
+        _ => Ok(FieldSelector::Glob(pattern, span))
+    }
+}
+
+impl FieldSelector {
+    pub fn span(&self) -> Span {
+        match self {
+            FieldSelector::Name(ident) => ident.span(),
+            FieldSelector::Glob(_, span) => *span,
+            FieldSelector::Type(ty) => ty.span(),
+            FieldSelector::Attribute(path) => path.span(),
+            FieldSelector::Not(selector) => selector.span()
+        }
+    }
+}
+
+// implement unit tests
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod tests {
+    use super::*;
+    use syn::{parse2, parse_quote};
+
+    #[test]
+    fn test_parse_type_selector() {
+        let input = parse_quote! { type: T };
+
+        let actual = parse2::<FieldSelector>(input).unwrap();
+
+        let expected = FieldSelector::Type(parse_quote!(T));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_type_pattern_selector() {
+        let input = parse_quote! { type: Option<_> };
+
+        let actual = parse2::<FieldSelector>(input).unwrap();
+
+        let expected = FieldSelector::Type(parse_quote!(Option<_>));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_name_selector() {
+        let input = parse_quote! { email };
+
+        let actual = parse2::<FieldSelector>(input).unwrap();
+
+        let expected = FieldSelector::Name(Ident::new("email", Span::call_site()));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_glob_selector() {
+        let prefix = parse2::<FieldSelector>(parse_quote! { addr_* }).unwrap();
+        let suffix = parse2::<FieldSelector>(parse_quote! { *_at }).unwrap();
+        let infix = parse2::<FieldSelector>(parse_quote! { created_*_utc }).unwrap();
+        let all = parse2::<FieldSelector>(parse_quote! { * }).unwrap();
+
+        assert_eq!(prefix, FieldSelector::Glob("addr_*".to_string(), Span::call_site()));
+        assert_eq!(suffix, FieldSelector::Glob("*_at".to_string(), Span::call_site()));
+        assert_eq!(infix, FieldSelector::Glob("created_*_utc".to_string(), Span::call_site()));
+        assert_eq!(all, FieldSelector::Glob("*".to_string(), Span::call_site()));
+    }
+
+    #[test]
+    fn test_parse_attribute_selector() {
+        let input = parse_quote! { #[pii] };
+
+        let actual = parse2::<FieldSelector>(input).unwrap();
+
+        let expected = FieldSelector::Attribute(parse_quote!(pii));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_attribute_selector_with_arguments() {
+        let input = parse_quote! { #[serde(skip)] };
+
+        let actual = parse2::<FieldSelector>(input);
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn test_parse_not_selector() {
+        let input = parse_quote! { !addr_* };
+
+        let actual = parse2::<FieldSelector>(input).unwrap();
+
+        let expected = FieldSelector::Not(Box::new(FieldSelector::Glob("addr_*".to_string(), Span::call_site())));
+        assert_eq!(actual, expected);
+        assert_eq!(actual.to_string(), "!addr_*");
+    }
+}
+
+impl Display for FieldSelector {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FieldSelector::Name(ident) => write!(f, "{}", ident),
+            FieldSelector::Glob(pattern, _) => write!(f, "{}", pattern),
+            FieldSelector::Type(ty) => write!(f, "type: {}", ty.to_token_stream()),
+            FieldSelector::Attribute(path) => write!(f, "#[{}]", path.to_token_stream()),
+            FieldSelector::Not(selector) => write!(f, "!{}", selector)
+        }
+    }
+}
+
+impl Debug for FieldSelector {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl PartialEq for FieldSelector {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_string() == other.to_string()
+    }
+}
@@ -1,63 +1,155 @@
-use std::fmt::{Debug, Formatter};
+use std::fmt::{Debug, Display, Formatter};
 use syn::punctuated::Punctuated;
-use syn::{braced, parenthesized, Token};
-use proc_macro2::{Ident, Span};
+use syn::{parenthesized, Token};
+use proc_macro2::Span;
 use syn::parse::{Parse, ParseStream, Result as ParseResult};
+use crate::parser::composite_struct::field_selector::FieldSelector;
+use crate::parser::composite_struct::type_expression::TypeExpression;
 
-pub struct AngleBracketedIdents {
-    pub items: Punctuated<Ident, Token![,]>,
+/// Parenthesized parameters of an operator, e.g. `(Pick(User, id), name)`. The operator decides
+/// which parameters are types and which are field names.
+pub struct OperatorParams {
+    pub items: Punctuated<OperatorParam, Token![,]>,
     pub span: Span
 }
 
-impl Parse for AngleBracketedIdents {
+/// A single operator parameter. Bare identifiers are parsed as [TypeExpression]s, since they may
+/// name either a type or a field, and everything else written in the selector syntax is parsed
+/// as a [FieldSelector].
+pub enum OperatorParam {
+    Type(TypeExpression),
+    Selector(FieldSelector),
+}
+
+impl Parse for OperatorParams {
     fn parse(input: ParseStream) -> ParseResult<Self> {
         let content;
-        parenthesized!(content in input);
+        let parens = parenthesized!(content in input);
         let items = Punctuated::parse_terminated(&content)?;
-        Ok(AngleBracketedIdents { items, span: input.span() })
+        Ok(OperatorParams { items, span: parens.span.join() })
+    }
+}
+
+impl Parse for OperatorParam {
+    fn parse(input: ParseStream) -> ParseResult<Self> {
+        let selector = input.peek(Token![type]) || input.peek(Token![!]) || input.peek(Token![#]) ||
+            input.peek(Token![*]) || (input.peek(syn::Ident) && input.peek2(Token![*]));
+        if selector {
+            return Ok(OperatorParam::Selector(input.parse()?))
+        }
+        Ok(OperatorParam::Type(input.parse()?))
+    }
+}
+
+impl OperatorParam {
+    pub fn span(&self) -> Span {
+        match self {
+            OperatorParam::Type(expression) => expression.span(),
+            OperatorParam::Selector(selector) => selector.span()
+        }
     }
 }
 
 // implement unit tests
 #[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod tests {
-    use proc_macro2::{Span, TokenStream};
+    use proc_macro2::Span;
     use syn::parse::Parser;
     use super::*;
     use syn::{parse2, parse_quote};
 
     #[test]
-    fn test_parse_angle_bracketed_idents() {
+    fn test_parse_operator_params() {
         let input = parse_quote! {
             (Ident, Ident2)
         };
 
-        let actual = parse2::<AngleBracketedIdents>(input).unwrap();
+        let actual = parse2::<OperatorParams>(input).unwrap();
 
-        let expected = AngleBracketedIdents {
+        let expected = OperatorParams {
             items: Punctuated::parse_terminated.parse2(parse_quote!(Ident, Ident2)).unwrap(),
             span: Span::call_site()
         };
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_parse_operator_params_nested() {
+        let input = parse_quote! {
+            (Required(Ident), Ident2)
+        };
+
+        let actual = parse2::<OperatorParams>(input).unwrap();
+
+        let expected = OperatorParams {
+            items: Punctuated::parse_terminated.parse2(parse_quote!(Required(Ident), Ident2)).unwrap(),
+            span: Span::call_site()
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_operator_params_selectors() {
+        let input = parse_quote! {
+            (User, addr_*, !id, #[pii], *_at)
+        };
+
+        let actual = parse2::<OperatorParams>(input).unwrap();
+
+        let expected = OperatorParams {
+            items: Punctuated::from_iter(vec![
+                OperatorParam::Type(parse_quote!(User)),
+                OperatorParam::Selector(parse_quote!(addr_*)),
+                OperatorParam::Selector(parse_quote!(!id)),
+                OperatorParam::Selector(parse_quote!(#[pii])),
+                OperatorParam::Selector(parse_quote!(*_at)),
+            ]),
+            span: Span::call_site()
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_operator_params_selector() {
+        let input = parse_quote! {
+            (Ident, type: T)
+        };
+
+        let actual = parse2::<OperatorParams>(input).unwrap();
+
+        let expected = OperatorParams {
+            items: Punctuated::from_iter(vec![
+                OperatorParam::Type(parse_quote!(Ident)),
+                OperatorParam::Selector(parse_quote!(type: T)),
+            ]),
+            span: Span::call_site()
+        };
+        assert_eq!(actual, expected);
+    }
 }
 
 
-impl Debug for AngleBracketedIdents {
+impl Display for OperatorParam {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OperatorParam::Type(expression) => write!(f, "{}", expression),
+            OperatorParam::Selector(selector) => write!(f, "{}", selector)
+        }
+    }
+}
+
+impl Debug for OperatorParams {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let params = self.items.iter().map(|i| i.to_string()).collect::<Vec<String>>();
-        write!(f, "AngleBracketedIdents {{ items: {:?} }}", params)
+        write!(f, "OperatorParams {{ items: {:?} }}", params)
     }
 }
 
-impl PartialEq for AngleBracketedIdents {
+impl PartialEq for OperatorParams {
     fn eq(&self, other: &Self) -> bool {
         let params_self = self.items.iter().map(|i| i.to_string()).collect::<Vec<String>>();
         let params_other = other.items.iter().map(|i| i.to_string()).collect::<Vec<String>>();
         params_self == params_other
     }
-
-    fn ne(&self, other: &Self) -> bool {
-        !self.eq(other)
-    }
 }
@@ -0,0 +1,149 @@
+use std::fmt::{Debug, Display, Formatter};
+use proc_macro2::{Group, Ident, TokenStream, TokenTree};
+use quote::ToTokens;
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::visit::Visit;
+use syn::{Token, Type};
+
+/// Metavariables such as `$T` can't be part of a [Type], so they are parsed as identifiers with
+/// this prefix instead.
+const METAVARIABLE_PREFIX: &str = "__compose_type_metavariable_";
+
+/// Rewrites the types matching `pattern` into `replacement` in `Map(Type, pattern => replacement)`.
+/// `_` in the pattern matches any type, and metavariables such as `$T` match any type and can be
+/// used in the replacement. Every metavariable of the replacement must be bound by the pattern.
+pub struct MapRule {
+    pub pattern: Type,
+    pub replacement: Type,
+}
+
+impl Parse for MapRule {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let pattern = input.parse()?;
+        input.parse::<Token![=>]>()?;
+        let rule = MapRule { pattern, replacement: input.parse()? };
+        let bound = Metavariables::of(&rule.pattern).into_iter()
+            .filter_map(metavariable)
+            .collect::<Vec<String>>();
+        for ty in Metavariables::of(&rule.replacement) {
+            if !metavariable(ty).is_some_and(|name| bound.contains(&name)) {
+                return Err(syn::Error::new(ty.span(), format!(
+                    "`{}` is not bound by the pattern `{}`", format_type(ty), format_type(&rule.pattern))))
+            }
+        }
+        Ok(rule)
+    }
+}
+
+/// Collects the metavariables of a type, in the order they appear.
+#[derive(Default)]
+struct Metavariables<'a> {
+    types: Vec<&'a Type>,
+}
+
+impl<'a> Metavariables<'a> {
+    fn of(ty: &'a Type) -> Vec<&'a Type> {
+        let mut metavariables = Metavariables::default();
+        metavariables.visit_type(ty);
+        metavariables.types
+    }
+}
+
+impl<'a> Visit<'a> for Metavariables<'a> {
+    fn visit_type(&mut self, ty: &'a Type) {
+        match metavariable(ty) {
+            Some(_) => self.types.push(ty),
+            None => syn::visit::visit_type(self, ty)
+        }
+    }
+}
+
+/// Replaces every `$T` in `tokens` with an identifier, so the tokens can be parsed as [Type]s.
+pub fn replace_metavariables(tokens: TokenStream) -> TokenStream {
+    let mut replaced = Vec::new();
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        match token {
+            TokenTree::Punct(punct) if punct.as_char() == '$' && matches!(tokens.peek(), Some(TokenTree::Ident(_))) => {
+                if let Some(TokenTree::Ident(name)) = tokens.next() {
+                    let ident = Ident::new(&format!("{}{}", METAVARIABLE_PREFIX, name), name.span());
+                    replaced.push(TokenTree::Ident(ident));
+                }
+            },
+            TokenTree::Group(group) => {
+                let mut new_group = Group::new(group.delimiter(), replace_metavariables(group.stream()));
+                new_group.set_span(group.span());
+                replaced.push(TokenTree::Group(new_group));
+            },
+            token => replaced.push(token)
+        }
+    }
+    replaced.into_iter().collect()
+}
+
+/// Name of the metavariable `ty` stands for, e.g. `T` for `$T`.
+pub fn metavariable(ty: &Type) -> Option<String> {
+    let Type::Path(type_path) = ty else { return None };
+    if type_path.qself.is_some() {
+        return None
+    }
+    let ident = type_path.path.get_ident()?.to_string();
+    ident.strip_prefix(METAVARIABLE_PREFIX).map(|name| name.to_string())
+}
+
+fn format_type(ty: &Type) -> String {
+    ty.to_token_stream().to_string().replace(METAVARIABLE_PREFIX, "$")
+}
+
+// implement unit tests
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+    use syn::parse::Parser;
+
+    #[test]
+    fn test_parse_map_rule() {
+        let input = replace_metavariables(parse_quote! { Option<$T> => Vec<$T> });
+
+        let actual = MapRule::parse.parse2(input).unwrap();
+
+        assert_eq!(metavariable(&parse_quote!(__compose_type_metavariable_T)), Some("T".to_string()));
+        assert_eq!(actual.to_string(), "Option < $T > => Vec < $T >");
+    }
+
+    #[test]
+    fn test_parse_map_rule_nested_metavariable() {
+        let input = replace_metavariables(parse_quote! { HashMap<$K, ($V, _)> => Vec<$V> });
+
+        let actual = MapRule::parse.parse2(input).unwrap();
+
+        assert_eq!(actual.to_string(), "HashMap < $K , ($V , _) > => Vec < $V >");
+    }
+
+    #[test]
+    fn test_metavariable() {
+        assert_eq!(metavariable(&parse_quote!(T)), None);
+        assert_eq!(metavariable(&parse_quote!(Vec<__compose_type_metavariable_T>)), None);
+    }
+}
+
+impl Display for MapRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} => {}", format_type(&self.pattern), format_type(&self.replacement))
+    }
+}
+
+impl Debug for MapRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl PartialEq for MapRule {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_string() == other.to_string()
+    }
+}
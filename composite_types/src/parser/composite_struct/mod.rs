@@ -1,17 +1,32 @@
 pub mod utility_operations;
+pub mod type_expression;
+pub mod field_selector;
+pub mod rename_rule;
+pub mod map_rule;
 mod helpers;
 
 use std::fmt::{Debug, Formatter};
 use proc_macro2::{Ident, Span};
 use quote::ToTokens;
+use syn::ext::IdentExt;
 use syn::parse::{Parse, ParseStream};
-use syn::{ItemStruct, Token};
-use crate::parser::composite_struct::utility_operations::UtilityOperation;
+use syn::{AngleBracketedGenericArguments, Attribute, Path, Token};
+use crate::parser::composite_struct::utility_operations::{ConflictPolicy, UtilityOperation};
+use crate::parser::composite_struct::type_expression::{format_path, TypeExpression};
+use crate::parser::settings::ItemSettings;
 
-/// Macro specific syntax to represent type composition operations.
+/// Macro specific syntax to represent type composition operations. Compositions producing an enum
+/// are declared with `enum` instead of `struct`, and those producing a type alias with `type`.
+#[allow(dead_code)]
 pub struct CompositeStruct {
+    /// Options given as `#[compose(...)]` attributes.
+    pub settings: ItemSettings,
+    /// Any other attributes, e.g. `#[derive(Debug)]`, which are added to the generated type.
+    pub attrs: Vec<Attribute>,
     pub pub_token: Option<Token![pub]>,
-    struct_token: Token![struct],
+    struct_token: Option<Token![struct]>,
+    pub enum_token: Option<Token![enum]>,
+    pub type_token: Option<Token![type]>,
     pub name: Ident,
     assignment_token: Token![=],
     pub composite_operation: CompositeOperation,
@@ -20,9 +35,25 @@ pub struct CompositeStruct {
 
 impl Parse for CompositeStruct {
     fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attrs = input.call(Attribute::parse_outer)?;
+        let settings = ItemSettings::take(&mut attrs)?;
+        let pub_token = input.parse().ok();
+        let enum_token: Option<Token![enum]> = input.parse()?;
+        let type_token: Option<Token![type]> = match enum_token {
+            None => input.parse()?,
+            Some(_) => None
+        };
+        let struct_token = match (enum_token, type_token) {
+            (None, None) => Some(input.parse()?),
+            _ => None
+        };
         Ok(CompositeStruct {
-            pub_token: input.parse().ok(),
-            struct_token: input.parse()?,
+            settings,
+            attrs,
+            pub_token,
+            struct_token,
+            enum_token,
+            type_token,
             name: input.parse()?,
             assignment_token: input.parse()?,
             composite_operation: input.parse()?,
@@ -33,24 +64,59 @@ impl Parse for CompositeStruct {
 
 pub enum CompositeOperation {
     TypeAlias(Ident),
+    /// `struct UserPage = Page<User>;` instantiates a generic type with concrete arguments.
+    GenericAlias(Ident, AngleBracketedGenericArguments),
+    /// `struct Admin = models::User;` copies a type declared in a module of the macro.
+    PathAlias(Path),
     UtilityOp(UtilityOperation),
+    /// `enum Shape = Circle | Square;` wraps each type in a tuple variant of a new enum.
+    Union(Vec<TypeExpression>),
+}
+
+impl CompositeOperation {
+    /// Type an alias names, e.g. `Page<User>` in `struct UserPage = Page<User>;`.
+    pub fn alias_target(&self) -> Option<TypeExpression> {
+        match self {
+            CompositeOperation::TypeAlias(ident) => Some(TypeExpression::Ident(ident.clone())),
+            CompositeOperation::GenericAlias(ident, arguments) => Some(TypeExpression::Generic(ident.clone(), arguments.clone())),
+            CompositeOperation::PathAlias(path) => Some(TypeExpression::Path(path.clone())),
+            CompositeOperation::UtilityOp(_) | CompositeOperation::Union(_) => None
+        }
+    }
 }
 
 impl Parse for CompositeOperation {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        return if let Ok(uo) = input.fork().parse::<UtilityOperation>() {
-            input.parse::<UtilityOperation>().ok();
-            Ok(CompositeOperation::UtilityOp(uo))
-        } else if input.peek(syn::Ident) {
-            let alias = input.parse::<Ident>()?;
-            Ok(CompositeOperation::TypeAlias(alias))
-        } else {
-            Err(input.error("Expected type alias or utility operation"))
-        };
+        if !input.peek(syn::Ident::peek_any) && !input.peek(Token![::]) {
+            return Err(input.error("Expected type alias or utility operation"))
+        }
+        let expression = input.parse::<TypeExpression>()?;
+        if input.peek(Token![|]) {
+            let mut expressions = vec![expression];
+            while input.parse::<Option<Token![|]>>()?.is_some() {
+                expressions.push(input.parse::<TypeExpression>()?);
+            }
+            return Ok(CompositeOperation::Union(expressions))
+        }
+        if !input.peek(Token![&]) {
+            return Ok(match expression {
+                TypeExpression::Ident(alias) => CompositeOperation::TypeAlias(alias),
+                TypeExpression::Generic(alias, arguments) => CompositeOperation::GenericAlias(alias, arguments),
+                TypeExpression::Path(path) => CompositeOperation::PathAlias(path),
+                TypeExpression::UtilityOp(uo) => CompositeOperation::UtilityOp(*uo)
+            })
+        }
+        // `A & B & ...` is shorthand for `Merge(A, B, ...)`
+        let mut expressions = vec![expression];
+        while input.parse::<Option<Token![&]>>()?.is_some() {
+            expressions.push(input.parse::<TypeExpression>()?);
+        }
+        Ok(CompositeOperation::UtilityOp(UtilityOperation::Merge(expressions, ConflictPolicy::Error)))
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod tests {
     use proc_macro2::Span;
     use super::*;
@@ -66,11 +132,15 @@ mod tests {
         let actual = parse2::<CompositeStruct>(input).unwrap();
 
         let expected = CompositeStruct {
+            settings: ItemSettings::default(),
+            attrs: Vec::new(),
             pub_token: Some(Token![pub](Span::call_site())),
-            struct_token: Token![struct](Span::call_site()),
+            struct_token: Some(Token![struct](Span::call_site())),
+            enum_token: None,
+            type_token: None,
             name: Ident::new("MyStruct", Span::call_site()),
             assignment_token: Token![=](Span::call_site()),
-            composite_operation: CompositeOperation::UtilityOp(UtilityOperation::Required(Ident::new("Ident", Span::call_site()))),
+            composite_operation: CompositeOperation::UtilityOp(UtilityOperation::Required(TypeExpression::Ident(Ident::new("Ident", Span::call_site())), vec![])),
             semi_colon: Some(Token![;](Span::call_site()))
         };
         assert_eq!(actual, expected);
@@ -85,8 +155,12 @@ mod tests {
         let actual = parse2::<CompositeStruct>(input).unwrap();
 
         let expected = CompositeStruct {
+            settings: ItemSettings::default(),
+            attrs: Vec::new(),
             pub_token: Some(Token![pub](Span::call_site())),
-            struct_token: Token![struct](Span::call_site()),
+            struct_token: Some(Token![struct](Span::call_site())),
+            enum_token: None,
+            type_token: None,
             name: Ident::new("MyStruct", Span::call_site()),
             assignment_token: Token![=](Span::call_site()),
             composite_operation: CompositeOperation::TypeAlias(Ident::new("MyType", Span::call_site())),
@@ -94,6 +168,119 @@ mod tests {
         };
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_parse_composite_struct_with_generic_alias() {
+        let input = parse_quote! {
+            struct UserPage = Page<User>;
+        };
+
+        let actual = parse2::<CompositeStruct>(input).unwrap();
+
+        let expected = CompositeStruct::new(
+            Ident::new("UserPage", Span::call_site()),
+            CompositeOperation::GenericAlias(Ident::new("Page", Span::call_site()), parse_quote!(<User>))
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_composite_struct_with_path_alias() {
+        let input = parse_quote! {
+            struct Admin = models::User;
+        };
+
+        let actual = parse2::<CompositeStruct>(input).unwrap();
+
+        let expected = CompositeStruct::new(
+            Ident::new("Admin", Span::call_site()),
+            CompositeOperation::PathAlias(parse_quote!(models::User))
+        );
+        assert_eq!(actual, expected);
+        assert_eq!(format!("{:?}", actual), "struct Admin = models::User");
+    }
+
+    #[test]
+    fn test_parse_composite_struct_with_attributes() {
+        let input = parse_quote! {
+            #[compose(internal)]
+            struct Base = Optional(User);
+        };
+
+        let actual = parse2::<CompositeStruct>(input).unwrap();
+        assert!(actual.settings.internal);
+
+        let input = parse_quote! {
+            #[derive(Debug)]
+            #[compose(internal)]
+            struct Base = Optional(User);
+        };
+
+        let actual = parse2::<CompositeStruct>(input).unwrap();
+        assert!(actual.settings.internal);
+        assert_eq!(actual.attrs.len(), 1);
+        assert!(actual.attrs[0].path().is_ident("derive"));
+    }
+
+    #[test]
+    fn test_parse_composite_enum() {
+        let input = parse_quote! {
+            pub enum StatePatch = Optional(State);
+        };
+
+        let actual = parse2::<CompositeStruct>(input).unwrap();
+
+        assert_eq!(actual.kind(), "enum");
+        assert_eq!(actual.name.to_string(), "StatePatch");
+        assert!(actual.pub_token.is_some());
+    }
+
+    #[test]
+    fn test_parse_composite_type() {
+        let input = parse_quote! {
+            pub type UserId = FieldType(User, id);
+        };
+
+        let actual = parse2::<CompositeStruct>(input).unwrap();
+
+        assert_eq!(actual.kind(), "type");
+        assert_eq!(actual.name.to_string(), "UserId");
+    }
+
+    #[test]
+    fn test_parse_composite_enum_with_union() {
+        let input = parse_quote! {
+            enum Shape = Circle | Square | Page<Triangle>;
+        };
+
+        let actual = parse2::<CompositeStruct>(input).unwrap();
+
+        assert_eq!(actual.kind(), "enum");
+        assert_eq!(actual.composite_operation, CompositeOperation::Union(vec![
+            TypeExpression::Ident(Ident::new("Circle", Span::call_site())),
+            TypeExpression::Ident(Ident::new("Square", Span::call_site())),
+            TypeExpression::Generic(Ident::new("Page", Span::call_site()), parse_quote!(<Triangle>)),
+        ]));
+    }
+
+    #[test]
+    fn test_parse_composite_struct_with_intersection() {
+        let input = parse_quote! {
+            struct Account = BankAccount & Audit & Owner;
+        };
+
+        let actual = parse2::<CompositeStruct>(input).unwrap();
+
+        let expected = CompositeStruct::new(
+            Ident::new("Account", Span::call_site()),
+            CompositeOperation::UtilityOp(UtilityOperation::Merge(vec![
+                TypeExpression::Ident(Ident::new("BankAccount", Span::call_site())),
+                TypeExpression::Ident(Ident::new("Audit", Span::call_site())),
+                TypeExpression::Ident(Ident::new("Owner", Span::call_site())),
+            ], ConflictPolicy::Error))
+        );
+        assert_eq!(actual, expected);
+    }
 }
 
 
@@ -101,24 +288,36 @@ impl PartialEq for CompositeOperation {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (CompositeOperation::TypeAlias(id1), CompositeOperation::TypeAlias(id2)) => id1 == id2,
+            (CompositeOperation::GenericAlias(id1, args1), CompositeOperation::GenericAlias(id2, args2)) => {
+                id1 == id2 && args1.to_token_stream().to_string() == args2.to_token_stream().to_string()
+            },
+            (CompositeOperation::PathAlias(path1), CompositeOperation::PathAlias(path2)) => {
+                format_path(path1) == format_path(path2)
+            },
             (CompositeOperation::UtilityOp(uo1), CompositeOperation::UtilityOp(uo2)) => uo1 == uo2,
+            (CompositeOperation::Union(members1), CompositeOperation::Union(members2)) => members1 == members2,
             _ => false,
         }
     }
-
-    fn ne(&self, other: &Self) -> bool {
-        !self.eq(other)
-    }
 }
 
 impl Debug for CompositeStruct {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match &self.composite_operation {
             CompositeOperation::TypeAlias(id) => {
-                write!(f, "struct {} = {}", self.name, id)
+                write!(f, "{} {} = {}", self.kind(), self.name, id)
+            },
+            CompositeOperation::GenericAlias(id, args) => {
+                write!(f, "{} {} = {}{}", self.kind(), self.name, id, args.to_token_stream())
+            },
+            CompositeOperation::PathAlias(path) => {
+                write!(f, "{} {} = {}", self.kind(), self.name, format_path(path))
             },
             CompositeOperation::UtilityOp(uo) => {
-                write!(f, "struct {} = {}", self.name, uo)
+                write!(f, "{} {} = {}", self.kind(), self.name, uo)
+            },
+            CompositeOperation::Union(members) => {
+                write!(f, "{} {} = {}", self.kind(), self.name, join_union(members))
             }
         }
     }
@@ -128,12 +327,10 @@ impl Debug for CompositeStruct {
 impl PartialEq for CompositeStruct {
     fn eq(&self, other: &Self) -> bool {
         self.name == other.name
+            && self.settings == other.settings
+            && self.kind() == other.kind()
             && self.composite_operation == other.composite_operation
     }
-
-    fn ne(&self, other: &Self) -> bool {
-        !self.eq(other)
-    }
 }
 
 impl Debug for CompositeOperation {
@@ -142,22 +339,59 @@ impl Debug for CompositeOperation {
             CompositeOperation::TypeAlias(id) => {
                 write!(f, "TypeAlias {}", id)
             },
+            CompositeOperation::GenericAlias(id, args) => {
+                write!(f, "GenericAlias {}{}", id, args.to_token_stream())
+            },
+            CompositeOperation::PathAlias(path) => {
+                write!(f, "PathAlias {}", format_path(path))
+            },
             CompositeOperation::UtilityOp(uo) => {
                 write!(f, "Utility {}", uo)
+            },
+            CompositeOperation::Union(members) => {
+                write!(f, "Union {}", join_union(members))
             }
         }
     }
 }
 
 impl CompositeStruct {
+    /// Kind of definition the composition is declared as, either `struct`, `enum` or `type`.
+    pub fn kind(&self) -> &'static str {
+        match (self.enum_token, self.type_token) {
+            (Some(_), _) => "enum",
+            (_, Some(_)) => "type",
+            _ => "struct"
+        }
+    }
+
+    /// Span of the `struct`, `enum` or `type` keyword the composition is declared with.
+    pub fn kind_span(&self) -> Span {
+        match (self.struct_token, self.enum_token, self.type_token) {
+            (Some(token), _, _) => token.span,
+            (_, Some(token), _) => token.span,
+            (_, _, Some(token)) => token.span,
+            _ => self.name.span()
+        }
+    }
+
+    #[allow(dead_code)]
     pub fn new(name: Ident, composite_operation: CompositeOperation) -> Self {
         CompositeStruct {
+            settings: ItemSettings::default(),
+            attrs: Vec::new(),
             pub_token: None,
-            struct_token: Token![struct](Span::call_site()),
+            struct_token: Some(Token![struct](Span::call_site())),
+            enum_token: None,
+            type_token: None,
             name,
             assignment_token: Token![=](Span::call_site()),
             composite_operation,
             semi_colon: Some(Token![;](Span::call_site())),
         }
     }
-}
\ No newline at end of file
+}
+
+fn join_union(members: &[TypeExpression]) -> String {
+    members.iter().map(|member| member.to_string()).collect::<Vec<String>>().join(" | ")
+}
@@ -0,0 +1,141 @@
+use std::fmt::{Debug, Display, Formatter};
+use proc_macro2::Ident;
+use syn::parse::{Parse, ParseStream};
+use syn::{LitStr, Token};
+
+/// Decides the new name of the fields of a struct in `Rename(Type, rule1, rule2, ...)`.
+pub enum RenameRule {
+    /// `old => new` renames a single field, ignoring every other rule.
+    Field(Ident, Ident),
+    /// `camelCase`, `PascalCase`, `snake_case` or `SCREAMING_SNAKE_CASE` converts the names of the
+    /// fields which are not renamed explicitly.
+    Convention(NamingConvention),
+    /// `prefix = "ffi_"` prepends to the names of the fields which are not renamed explicitly.
+    Prefix(LitStr),
+    /// `suffix = "_raw"` appends to the names of the fields which are not renamed explicitly.
+    Suffix(LitStr),
+}
+
+/// Naming conventions supported by [RenameRule::Convention], named after themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NamingConvention {
+    /// `camelCase`
+    LowerCamel,
+    /// `PascalCase`, also accepted as `CamelCase`
+    UpperCamel,
+    /// `snake_case`
+    Snake,
+    /// `SCREAMING_SNAKE_CASE`
+    ScreamingSnake,
+}
+
+impl Parse for RenameRule {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek2(Token![=>]) {
+            let old = input.parse()?;
+            input.parse::<Token![=>]>()?;
+            return Ok(RenameRule::Field(old, input.parse()?))
+        }
+        let key = input.parse::<Ident>()?;
+        if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            return match key.to_string().as_str() {
+                "prefix" => Ok(RenameRule::Prefix(input.parse()?)),
+                "suffix" => Ok(RenameRule::Suffix(input.parse()?)),
+                _ => Err(syn::Error::new(key.span(), "Expected `prefix = \"...\"` or `suffix = \"...\"`"))
+            }
+        }
+        let convention = match key.to_string().as_str() {
+            "camelCase" => NamingConvention::LowerCamel,
+            "PascalCase" | "CamelCase" => NamingConvention::UpperCamel,
+            "snake_case" => NamingConvention::Snake,
+            "SCREAMING_SNAKE_CASE" => NamingConvention::ScreamingSnake,
+            _ => return Err(syn::Error::new(key.span(),
+                "Expected `old => new`, `prefix = \"...\"`, `suffix = \"...\"` or one of `camelCase`, `PascalCase`, `snake_case` or `SCREAMING_SNAKE_CASE`"))
+        };
+        Ok(RenameRule::Convention(convention))
+    }
+}
+
+// implement unit tests
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod tests {
+    use super::*;
+    use proc_macro2::Span;
+    use syn::{parse2, parse_quote};
+
+    #[test]
+    fn test_parse_field_rule() {
+        let input = parse_quote! { name => display_name };
+
+        let actual = parse2::<RenameRule>(input).unwrap();
+
+        let expected = RenameRule::Field(
+            Ident::new("name", Span::call_site()),
+            Ident::new("display_name", Span::call_site())
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_convention_rule() {
+        let lower = parse2::<RenameRule>(parse_quote! { camelCase }).unwrap();
+        let upper = parse2::<RenameRule>(parse_quote! { CamelCase }).unwrap();
+
+        assert_eq!(lower, RenameRule::Convention(NamingConvention::LowerCamel));
+        assert_eq!(upper, RenameRule::Convention(NamingConvention::UpperCamel));
+    }
+
+    #[test]
+    fn test_parse_affix_rule() {
+        let input = parse_quote! { prefix = "ffi_" };
+
+        let actual = parse2::<RenameRule>(input).unwrap();
+
+        let expected = RenameRule::Prefix(LitStr::new("ffi_", Span::call_site()));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_unknown_rule() {
+        let input = parse_quote! { kebab };
+
+        let actual = parse2::<RenameRule>(input);
+        assert!(actual.is_err());
+    }
+}
+
+impl Display for NamingConvention {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NamingConvention::LowerCamel => write!(f, "camelCase"),
+            NamingConvention::UpperCamel => write!(f, "PascalCase"),
+            NamingConvention::Snake => write!(f, "snake_case"),
+            NamingConvention::ScreamingSnake => write!(f, "SCREAMING_SNAKE_CASE"),
+        }
+    }
+}
+
+impl Display for RenameRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RenameRule::Field(old, new) => write!(f, "{} => {}", old, new),
+            RenameRule::Convention(convention) => write!(f, "{}", convention),
+            RenameRule::Prefix(affix) => write!(f, "prefix = {:?}", affix.value()),
+            RenameRule::Suffix(affix) => write!(f, "suffix = {:?}", affix.value())
+        }
+    }
+}
+
+impl Debug for RenameRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl PartialEq for RenameRule {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_string() == other.to_string()
+    }
+}
@@ -0,0 +1,179 @@
+use std::fmt::{Debug, Display, Formatter};
+use proc_macro2::{Ident, Span};
+use quote::ToTokens;
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::{token, AngleBracketedGenericArguments, ItemType, Path, PathArguments, Token, Type};
+use crate::parser::composite_struct::utility_operations::UtilityOperation;
+
+/// A type operand of a composition, e.g. `User` or `Pick(User, id)` in `Optional(Pick(User, id))`.
+/// Operations used as operands are evaluated in place and never emitted as their own type.
+pub enum TypeExpression {
+    Ident(Ident),
+    /// A generic type instantiated with concrete arguments, e.g. `Page<User>`.
+    Generic(Ident, AngleBracketedGenericArguments),
+    /// A type declared in a module of the macro, e.g. `models::User` or `super::Page<User>`.
+    Path(Path),
+    UtilityOp(Box<UtilityOperation>),
+}
+
+impl Parse for TypeExpression {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(syn::Ident) && input.peek2(token::Paren) {
+            return Ok(TypeExpression::UtilityOp(Box::new(input.parse()?)))
+        }
+        if input.peek(Token![::]) || (input.peek(syn::Ident::peek_any) && input.peek2(Token![::])) {
+            return Ok(TypeExpression::Path(input.parse()?))
+        }
+        if input.peek(syn::Ident) && input.peek2(Token![<]) {
+            return Ok(TypeExpression::Generic(input.parse()?, input.parse()?))
+        }
+        Ok(TypeExpression::Ident(input.parse()?))
+    }
+}
+
+impl TypeExpression {
+    /// Span of the type named by the expression. For operations, this is the span of the
+    /// first type they operate on.
+    pub fn span(&self) -> Span {
+        match self {
+            TypeExpression::Ident(ident) => ident.span(),
+            TypeExpression::Generic(ident, _) => ident.span(),
+            TypeExpression::Path(path) => path.span(),
+            TypeExpression::UtilityOp(uo) => uo.span()
+        }
+    }
+
+    /// The type a plain alias like `type Account = BankAccount;` names, if it could be a type
+    /// declared in the macro. Aliases with generic parameters, and of types which aren't paths,
+    /// e.g. `(u8, u8)` or `<T as Trait>::Output`, have none.
+    pub fn from_alias(alias: &ItemType) -> Option<Self> {
+        let Type::Path(type_path) = alias.ty.as_ref() else { return None };
+        let path = &type_path.path;
+        if !alias.generics.params.is_empty() || type_path.qself.is_some()
+            || path.segments.iter().rev().skip(1).any(|segment| !segment.arguments.is_none()) {
+            return None
+        }
+        let last = path.segments.last()?;
+        match &last.arguments {
+            PathArguments::Parenthesized(_) => None,
+            _ if path.leading_colon.is_some() || path.segments.len() > 1 => Some(TypeExpression::Path(path.clone())),
+            PathArguments::None => Some(TypeExpression::Ident(last.ident.clone())),
+            PathArguments::AngleBracketed(arguments) => Some(TypeExpression::Generic(last.ident.clone(), arguments.clone()))
+        }
+    }
+}
+
+// implement unit tests
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod tests {
+    use super::*;
+    use crate::parser::composite_struct::field_selector::FieldSelector;
+    use syn::{parse2, parse_quote};
+
+    #[test]
+    fn test_parse_type_expression_ident() {
+        let input = parse_quote! { User };
+
+        let actual = parse2::<TypeExpression>(input).unwrap();
+
+        let expected = TypeExpression::Ident(Ident::new("User", Span::call_site()));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_type_expression_nested() {
+        let input = parse_quote! { Optional(Pick(User, id)) };
+
+        let actual = parse2::<TypeExpression>(input).unwrap();
+
+        let expected = TypeExpression::UtilityOp(Box::new(UtilityOperation::Optional(
+            TypeExpression::UtilityOp(Box::new(UtilityOperation::Pick(
+                TypeExpression::Ident(Ident::new("User", Span::call_site())),
+                vec![FieldSelector::Name(Ident::new("id", Span::call_site()))]
+            ))),
+            vec![]
+        )));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_type_expression_generic() {
+        let input = parse_quote! { Optional(Page<'static, User, 10>) };
+
+        let actual = parse2::<TypeExpression>(input).unwrap();
+
+        let expected = TypeExpression::UtilityOp(Box::new(UtilityOperation::Optional(
+            TypeExpression::Generic(
+                Ident::new("Page", Span::call_site()),
+                parse_quote!(<'static, User, 10>)
+            ),
+            vec![]
+        )));
+        assert_eq!(actual, expected);
+        assert_eq!(actual.to_string(), "Optional<Page<'static, User, 10>>");
+    }
+
+    #[test]
+    fn test_parse_type_expression_path() {
+        let input = parse_quote! { Pick(super::models::Page<User>, id) };
+
+        let actual = parse2::<TypeExpression>(input).unwrap();
+
+        let expected = TypeExpression::UtilityOp(Box::new(UtilityOperation::Pick(
+            TypeExpression::Path(parse_quote!(super::models::Page<User>)),
+            vec![FieldSelector::Name(Ident::new("id", Span::call_site()))]
+        )));
+        assert_eq!(actual, expected);
+        assert_eq!(actual.to_string(), "Pick<super::models::Page<User>, id>");
+    }
+}
+
+impl Display for TypeExpression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TypeExpression::Ident(ident) => write!(f, "{}", ident),
+            TypeExpression::Generic(ident, arguments) => write!(f, "{}{}", ident, format_arguments(arguments)),
+            TypeExpression::Path(path) => write!(f, "{}", format_path(path)),
+            TypeExpression::UtilityOp(uo) => write!(f, "{}", uo)
+        }
+    }
+}
+
+fn format_arguments(arguments: &AngleBracketedGenericArguments) -> String {
+    let arguments = arguments.args.iter()
+        .map(|argument| argument.to_token_stream().to_string())
+        .collect::<Vec<String>>();
+    format!("<{}>", arguments.join(", "))
+}
+
+/// Formats `path` the way it is written, e.g. `models::Page<User>`.
+pub fn format_path(path: &Path) -> String {
+    let segments = path.segments.iter().map(|segment| match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) => format!("{}{}", segment.ident, format_arguments(arguments)),
+        _ => segment.ident.to_string()
+    }).collect::<Vec<String>>();
+    format!("{}{}", if path.leading_colon.is_some() { "::" } else { "" }, segments.join("::"))
+}
+
+impl Debug for TypeExpression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl PartialEq for TypeExpression {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (TypeExpression::Ident(ident1), TypeExpression::Ident(ident2)) => ident1 == ident2,
+            (TypeExpression::Generic(ident1, arguments1), TypeExpression::Generic(ident2, arguments2)) => {
+                ident1 == ident2 && arguments1.to_token_stream().to_string() == arguments2.to_token_stream().to_string()
+            },
+            (TypeExpression::Path(path1), TypeExpression::Path(path2)) => format_path(path1) == format_path(path2),
+            (TypeExpression::UtilityOp(uo1), TypeExpression::UtilityOp(uo2)) => uo1 == uo2,
+            _ => false
+        }
+    }
+}
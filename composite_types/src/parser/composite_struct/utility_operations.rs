@@ -1,44 +1,330 @@
 use std::fmt::{Debug, Display, Formatter};
 use proc_macro2::Ident;
-use syn::parse::{Parse, ParseStream};
-use crate::parser::composite_struct::helpers::AngleBracketedIdents;
+use syn::parse::{Parse, Parser, ParseStream};
+use syn::{parenthesized, Path, Token};
+use proc_macro2::Span;
+use crate::parser::composite_struct::field_selector::FieldSelector;
+use crate::parser::composite_struct::helpers::{OperatorParam, OperatorParams};
+use crate::parser::composite_struct::rename_rule::RenameRule;
+use crate::parser::composite_struct::map_rule::{MapRule, replace_metavariables};
+use crate::parser::composite_struct::type_expression::{format_path, TypeExpression};
 
 pub enum UtilityOperation {
-    Required(Ident),
-    Optional(Ident),
+    /// Unwraps `Option` fields of the type, optionally restricted by selectors:
+    /// `Required(Type, field, type: T, ...)`
+    Required(TypeExpression, Vec<FieldSelector>),
+    /// Wraps fields of the type in `Option`, optionally restricted by selectors:
+    /// `Optional(Type, field, type: T, ...)`
+    Optional(TypeExpression, Vec<FieldSelector>),
+    /// Keeps only the selected fields of the type: `Pick(Type, field, addr_*, ...)`
+    Pick(TypeExpression, Vec<FieldSelector>),
+    /// Drops the selected fields of the type: `Omit(Type, field, #[pii], ...)`
+    Omit(TypeExpression, Vec<FieldSelector>),
+    /// Combines the fields of every listed type: `Merge(Type1, Type2, ..., conflict = error)`
+    Merge(Vec<TypeExpression>, ConflictPolicy),
+    /// Generates a fieldless enum with one variant per field of the type: `KeyOf(Type)`
+    KeyOf(TypeExpression),
+    /// Type alias for the declared type of a field: `FieldType(Type, field)`
+    FieldType(TypeExpression, Ident),
+    /// Renames the fields of the type: `Rename(Type, old => new, camelCase, prefix = "ffi_", ...)`
+    Rename(TypeExpression, Vec<RenameRule>),
+    /// Wraps fields of the type in a generic type, optionally restricted by selectors:
+    /// `Wrap(Type, Arc, field, ...)`, where the wrapper may be a path such as `std::sync::Arc`
+    Wrap(TypeExpression, Path, Vec<FieldSelector>),
+    /// Unwraps fields of the type from a generic type, optionally restricted by selectors:
+    /// `Unwrap(Type, Box, field, ...)`, where the wrapper may be a path such as `std::boxed::Box`
+    Unwrap(TypeExpression, Path, Vec<FieldSelector>),
+    /// Rewrites the field types of the type by the first matching rule:
+    /// `Map(Type, Option<$T> => Vec<$T>, ...)`
+    Map(TypeExpression, Vec<MapRule>),
+    /// Wraps fields of the type in `Option`, and of the types it contains which are defined in the
+    /// same macro: `DeepOptional(Type)`
+    DeepOptional(TypeExpression),
+    /// Unwraps `Option` fields of the type, and of the types it contains which are defined in the
+    /// same macro: `DeepRequired(Type)`
+    DeepRequired(TypeExpression),
+}
+
+/// Decides which field is kept when merged types define a field with the same name. Variants of
+/// merged enums are resolved the same way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Duplicate field names are a compile error.
+    Error,
+    /// The field of the earliest listed type wins. Fields of later types with the same name are
+    /// dropped along with their types and attributes, even if their types differ.
+    First,
+    /// The field of the latest listed type wins, at the position of the first field with its
+    /// name. Fields of earlier types with the same name are dropped along with their types and
+    /// attributes, even if their types differ.
+    Last,
+}
+
+impl Parse for ConflictPolicy {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key = input.parse::<Ident>()?;
+        if key != "conflict" {
+            return Err(syn::Error::new(key.span(), "Expected `conflict = error | first | last`"))
+        }
+        input.parse::<Token![=]>()?;
+        let policy = input.parse::<Ident>()?;
+        match policy.to_string().as_str() {
+            "error" => Ok(ConflictPolicy::Error),
+            "first" => Ok(ConflictPolicy::First),
+            "last" => Ok(ConflictPolicy::Last),
+            _ => Err(syn::Error::new(policy.span(), "Expected one of `error`, `first` or `last`"))
+        }
+    }
+}
+
+impl Display for ConflictPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConflictPolicy::Error => write!(f, "error"),
+            ConflictPolicy::First => write!(f, "first"),
+            ConflictPolicy::Last => write!(f, "last"),
+        }
+    }
 }
 
 impl Parse for UtilityOperation {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let operator = input.parse::<Ident>()?;
-        let params = AngleBracketedIdents::parse(input)?;
         match operator.to_string().as_str() {
             "Required" => {
-                let param = validate_one_param(params)?;
-                return Ok(UtilityOperation::Required(param))
+                let (param, selectors) = validate_param_with_selectors(OperatorParams::parse(input)?)?;
+                Ok(UtilityOperation::Required(param, selectors))
             },
             "Optional" => {
-                let param = validate_one_param(params)?;
-                return Ok(UtilityOperation::Optional(param))
+                let (param, selectors) = validate_param_with_selectors(OperatorParams::parse(input)?)?;
+                Ok(UtilityOperation::Optional(param, selectors))
+            },
+            "Pick" => {
+                let (param, selectors) = validate_param_with_required_selectors(OperatorParams::parse(input)?)?;
+                Ok(UtilityOperation::Pick(param, selectors))
+            },
+            "Omit" => {
+                let (param, selectors) = validate_param_with_required_selectors(OperatorParams::parse(input)?)?;
+                Ok(UtilityOperation::Omit(param, selectors))
+            },
+            "Merge" => {
+                let (params, conflict) = parse_merge_params(input)?;
+                Ok(UtilityOperation::Merge(params, conflict))
+            },
+            "KeyOf" => {
+                let param = validate_single_type_param(OperatorParams::parse(input)?)?;
+                Ok(UtilityOperation::KeyOf(param))
+            },
+            "FieldType" => {
+                let params = OperatorParams::parse(input)?;
+                let span = params.span;
+                let (param, mut fields) = validate_param_with_fields(params)?;
+                if fields.len() != 1 {
+                    return Err(syn::Error::new(span, format!(
+                        "Expected a type followed by a single field, but instead found {} fields", fields.len())));
+                }
+                Ok(UtilityOperation::FieldType(param, fields.remove(0)))
+            },
+            "Rename" => {
+                let (param, rules) = parse_rename_params(input)?;
+                Ok(UtilityOperation::Rename(param, rules))
+            },
+            "Wrap" => {
+                let (param, wrapper, selectors) = validate_param_with_wrapper(OperatorParams::parse(input)?)?;
+                Ok(UtilityOperation::Wrap(param, wrapper, selectors))
+            },
+            "Unwrap" => {
+                let (param, wrapper, selectors) = validate_param_with_wrapper(OperatorParams::parse(input)?)?;
+                Ok(UtilityOperation::Unwrap(param, wrapper, selectors))
+            },
+            "Map" => {
+                let (param, rules) = parse_map_params(input)?;
+                Ok(UtilityOperation::Map(param, rules))
+            },
+            "DeepOptional" => {
+                let param = validate_single_type_param(OperatorParams::parse(input)?)?;
+                Ok(UtilityOperation::DeepOptional(param))
+            },
+            "DeepRequired" => {
+                let param = validate_single_type_param(OperatorParams::parse(input)?)?;
+                Ok(UtilityOperation::DeepRequired(param))
             },
             _ => {
-                Err(syn::Error::new(operator.span(), "Expected Required or Optional"))
+                Err(syn::Error::new(operator.span(), "Expected Required, Optional, Pick, Omit, Merge, KeyOf, FieldType, Rename, Wrap, Unwrap, Map, DeepOptional or DeepRequired"))
             }
         }
     }
 }
 
-fn validate_one_param(params: AngleBracketedIdents) -> syn::Result<Ident> {
+impl UtilityOperation {
+    /// Span of the first type the operation is applied to.
+    pub fn span(&self) -> Span {
+        match self {
+            UtilityOperation::Required(expr, _) | UtilityOperation::Optional(expr, _) |
+            UtilityOperation::Pick(expr, _) | UtilityOperation::Omit(expr, _) => expr.span(),
+            UtilityOperation::KeyOf(expr) | UtilityOperation::FieldType(expr, _) |
+            UtilityOperation::Rename(expr, _) | UtilityOperation::Wrap(expr, _, _) |
+            UtilityOperation::Unwrap(expr, _, _) | UtilityOperation::Map(expr, _) |
+            UtilityOperation::DeepOptional(expr) | UtilityOperation::DeepRequired(expr) => expr.span(),
+            UtilityOperation::Merge(exprs, _) => exprs[0].span()
+        }
+    }
+}
+
+fn validate_type_param(param: OperatorParam) -> syn::Result<TypeExpression> {
+    match param {
+        OperatorParam::Type(expression) => Ok(expression),
+        OperatorParam::Selector(selector) => Err(syn::Error::new(selector.span(), "Expected a type"))
+    }
+}
+
+/// Expects exactly one type, e.g. `(Type)`.
+fn validate_single_type_param(params: OperatorParams) -> syn::Result<TypeExpression> {
     let num_params = params.items.len();
     if num_params != 1 {
         return Err(syn::Error::new(params.span,
-                            format!("Expected 1 parameter, but instead found {}", num_params)));
+                            format!("Expected a single type, but instead found {} parameters", num_params)));
+    }
+    validate_type_param(params.items.into_iter().next().unwrap())
+}
+
+/// Splits `(Type, selector1, selector2, ...)` into the type and its selectors, where bare
+/// identifiers select fields by name. There may be no selectors at all.
+fn validate_param_with_selectors(params: OperatorParams) -> syn::Result<(TypeExpression, Vec<FieldSelector>)> {
+    let mut items = params.items.into_iter();
+    let param = match items.next() {
+        None => return Err(syn::Error::new(params.span, "Expected a type, but instead found 0 parameters")),
+        Some(param) => validate_type_param(param)?
+    };
+    Ok((param, validate_selectors(items)?))
+}
+
+/// Splits `(Type, selector1, selector2, ...)` into the type and its selectors, where at least one
+/// selector is required.
+fn validate_param_with_required_selectors(params: OperatorParams) -> syn::Result<(TypeExpression, Vec<FieldSelector>)> {
+    let num_params = params.items.len();
+    if num_params < 2 {
+        return Err(syn::Error::new(params.span,
+                            format!("Expected a type followed by at least 1 field selector, but instead found {} parameters", num_params)));
+    }
+    validate_param_with_selectors(params)
+}
+
+/// Splits `(Type, Wrapper, selector1, selector2, ...)` into the type, the path of the wrapping
+/// type without generic arguments and the selectors. There may be no selectors at all.
+fn validate_param_with_wrapper(params: OperatorParams) -> syn::Result<(TypeExpression, Path, Vec<FieldSelector>)> {
+    let num_params = params.items.len();
+    if num_params < 2 {
+        return Err(syn::Error::new(params.span,
+                            format!("Expected a type followed by a wrapper type such as `Box`, but instead found {} parameters", num_params)));
+    }
+    let mut items = params.items.into_iter();
+    let param = validate_type_param(items.next().unwrap())?;
+    let wrapper = match items.next().unwrap() {
+        OperatorParam::Type(TypeExpression::Ident(wrapper)) => Path::from(wrapper),
+        OperatorParam::Type(TypeExpression::Path(wrapper))
+            if wrapper.segments.iter().all(|segment| segment.arguments.is_none()) => wrapper,
+        item => return Err(syn::Error::new(item.span(), "Expected the path of a wrapper type such as `Box` or `std::sync::Arc`"))
+    };
+    Ok((param, wrapper, validate_selectors(items)?))
+}
+
+fn validate_selectors(items: impl Iterator<Item = OperatorParam>) -> syn::Result<Vec<FieldSelector>> {
+    items.map(|item| match item {
+        OperatorParam::Selector(selector) => Ok(selector),
+        OperatorParam::Type(TypeExpression::Ident(ident)) => Ok(FieldSelector::Name(ident)),
+        OperatorParam::Type(expression) => Err(syn::Error::new(expression.span(),
+                                                               "Expected a field name or a field selector such as `type: T`"))
+    }).collect()
+}
+
+/// Splits `(Type, field1, field2, ...)` into the type and its field list.
+fn validate_param_with_fields(params: OperatorParams) -> syn::Result<(TypeExpression, Vec<Ident>)> {
+    let num_params = params.items.len();
+    if num_params < 2 {
+        return Err(syn::Error::new(params.span,
+                            format!("Expected a type followed by at least 1 field, but instead found {} parameters", num_params)));
     }
-    Ok(params.items.first().unwrap().to_owned())
+    let mut items = params.items.into_iter();
+    let param = validate_type_param(items.next().unwrap())?;
+    let fields = items.map(|item| match item {
+        OperatorParam::Type(TypeExpression::Ident(ident)) => Ok(ident),
+        item => Err(syn::Error::new(item.span(), "Expected a field name"))
+    }).collect::<syn::Result<Vec<Ident>>>()?;
+    Ok((param, fields))
+}
+
+/// Parses `(Type1, Type2, ..., conflict = policy)`, where the conflict policy is optional and
+/// defaults to [ConflictPolicy::Error].
+fn parse_merge_params(input: ParseStream) -> syn::Result<(Vec<TypeExpression>, ConflictPolicy)> {
+    let content;
+    let parens = parenthesized!(content in input);
+    let mut params = Vec::new();
+    let mut conflict = ConflictPolicy::Error;
+    while !content.is_empty() {
+        if content.peek2(Token![=]) {
+            conflict = content.parse()?;
+        } else {
+            params.push(content.parse::<TypeExpression>()?);
+        }
+        if content.is_empty() {
+            break
+        }
+        content.parse::<Token![,]>()?;
+    }
+    if params.len() < 2 {
+        return Err(syn::Error::new(parens.span.join(),
+                            format!("Expected at least 2 types to merge, but instead found {}", params.len())));
+    }
+    Ok((params, conflict))
+}
+
+/// Parses `(Type, rule1, rule2, ...)`, where at least one [RenameRule] is required.
+fn parse_rename_params(input: ParseStream) -> syn::Result<(TypeExpression, Vec<RenameRule>)> {
+    let content;
+    let parens = parenthesized!(content in input);
+    let param = content.parse::<TypeExpression>()?;
+    let mut rules = Vec::new();
+    while !content.is_empty() {
+        content.parse::<Token![,]>()?;
+        if content.is_empty() {
+            break
+        }
+        rules.push(content.parse::<RenameRule>()?);
+    }
+    if rules.is_empty() {
+        return Err(syn::Error::new(parens.span.join(), "Expected a type followed by at least 1 rename rule"));
+    }
+    Ok((param, rules))
+}
+
+/// Parses `(Type, rule1, rule2, ...)`, where at least one [MapRule] is required. Metavariables
+/// are replaced before parsing, so the rules can be parsed as types.
+fn parse_map_params(input: ParseStream) -> syn::Result<(TypeExpression, Vec<MapRule>)> {
+    let content;
+    let parens = parenthesized!(content in input);
+    let tokens = replace_metavariables(content.parse()?);
+    let (param, rules) = (|input: ParseStream| {
+        let param = input.parse::<TypeExpression>()?;
+        let mut rules = Vec::new();
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break
+            }
+            rules.push(input.parse::<MapRule>()?);
+        }
+        Ok((param, rules))
+    }).parse2(tokens)?;
+    if rules.is_empty() {
+        return Err(syn::Error::new(parens.span.join(), "Expected a type followed by at least 1 rule such as `Option<$T> => $T`"));
+    }
+    Ok((param, rules))
 }
 
 // implement unit tests
 #[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod tests {
     use proc_macro2::Span;
     use super::*;
@@ -52,22 +338,393 @@ mod tests {
 
         let actual = parse2::<UtilityOperation>(input).unwrap();
 
-        let expected = UtilityOperation::Required(Ident::new("Ident", Span::call_site()));
+        let expected = UtilityOperation::Required(TypeExpression::Ident(Ident::new("Ident", Span::call_site())), vec![]);
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_parse_utility_operation_with_selector() {
+        let input = parse_quote! {
+            Optional(Page, type: T)
+        };
+
+        let actual = parse2::<UtilityOperation>(input).unwrap();
+
+        let expected = UtilityOperation::Optional(
+            TypeExpression::Ident(Ident::new("Page", Span::call_site())),
+            vec![FieldSelector::Type(parse_quote!(T))]
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_utility_operation_with_field_names() {
+        let input = parse_quote! {
+            Optional(User, email, phone)
+        };
+
+        let actual = parse2::<UtilityOperation>(input).unwrap();
+
+        let expected = UtilityOperation::Optional(
+            TypeExpression::Ident(Ident::new("User", Span::call_site())),
+            vec![
+                FieldSelector::Name(Ident::new("email", Span::call_site())),
+                FieldSelector::Name(Ident::new("phone", Span::call_site()))
+            ]
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_pick() {
+        let input = parse_quote! {
+            Pick(User, id, name)
+        };
+
+        let actual = parse2::<UtilityOperation>(input).unwrap();
+
+        let expected = UtilityOperation::Pick(
+            TypeExpression::Ident(Ident::new("User", Span::call_site())),
+            vec![FieldSelector::Name(Ident::new("id", Span::call_site())), FieldSelector::Name(Ident::new("name", Span::call_site()))]
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_omit() {
+        let input = parse_quote! {
+            Omit(User, id)
+        };
+
+        let actual = parse2::<UtilityOperation>(input).unwrap();
+
+        let expected = UtilityOperation::Omit(
+            TypeExpression::Ident(Ident::new("User", Span::call_site())),
+            vec![FieldSelector::Name(Ident::new("id", Span::call_site()))]
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_omit_with_selectors() {
+        let input = parse_quote! {
+            Omit(User, addr_*, #[pii], !addr_city)
+        };
+
+        let actual = parse2::<UtilityOperation>(input).unwrap();
+
+        let expected = UtilityOperation::Omit(
+            TypeExpression::Ident(Ident::new("User", Span::call_site())),
+            vec![parse_quote!(addr_*), parse_quote!(#[pii]), parse_quote!(!addr_city)]
+        );
+        assert_eq!(actual, expected);
+        assert_eq!(actual.to_string(), "Omit<User, addr_*, #[pii], !addr_city>");
+    }
+
+    #[test]
+    fn test_parse_merge() {
+        let input = parse_quote! {
+            Merge(BankAccount, Audit, conflict = last)
+        };
+
+        let actual = parse2::<UtilityOperation>(input).unwrap();
+
+        let expected = UtilityOperation::Merge(
+            vec![
+                TypeExpression::Ident(Ident::new("BankAccount", Span::call_site())),
+                TypeExpression::Ident(Ident::new("Audit", Span::call_site()))
+            ],
+            ConflictPolicy::Last
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_merge_invalid_policy() {
+        let input = parse_quote! {
+            Merge(BankAccount, Audit, conflict = newest)
+        };
+
+        let actual = parse2::<UtilityOperation>(input);
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn test_parse_nested_utility_operation() {
+        let input = parse_quote! {
+            Merge(Optional(Pick(User, id)), Audit)
+        };
+
+        let actual = parse2::<UtilityOperation>(input).unwrap();
+
+        let expected = UtilityOperation::Merge(
+            vec![
+                parse_quote!(Optional(Pick(User, id))),
+                TypeExpression::Ident(Ident::new("Audit", Span::call_site()))
+            ],
+            ConflictPolicy::Error
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_pick_with_nested_field() {
+        let input = parse_quote! {
+            Pick(User, Required(id))
+        };
+
+        let actual = parse2::<UtilityOperation>(input);
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn test_parse_pick_without_fields() {
+        let input = parse_quote! {
+            Pick(User)
+        };
+
+        let actual = parse2::<UtilityOperation>(input);
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn test_parse_key_of() {
+        let input = parse_quote! {
+            KeyOf(Omit(User, id))
+        };
+
+        let actual = parse2::<UtilityOperation>(input).unwrap();
+
+        let expected = UtilityOperation::KeyOf(TypeExpression::UtilityOp(Box::new(UtilityOperation::Omit(
+            TypeExpression::Ident(Ident::new("User", Span::call_site())),
+            vec![FieldSelector::Name(Ident::new("id", Span::call_site()))]
+        ))));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_key_of_with_fields() {
+        let input = parse_quote! {
+            KeyOf(User, id)
+        };
+
+        let actual = parse2::<UtilityOperation>(input);
+        assert_eq!(actual.err().unwrap().to_string(), "Expected a single type, but instead found 2 parameters");
+    }
+
+    #[test]
+    fn test_parse_deep_optional() {
+        let input = parse_quote! {
+            DeepOptional(User)
+        };
+
+        let actual = parse2::<UtilityOperation>(input).unwrap();
+
+        let expected = UtilityOperation::DeepOptional(TypeExpression::Ident(Ident::new("User", Span::call_site())));
+        assert_eq!(actual, expected);
+        assert_ne!(actual, UtilityOperation::DeepRequired(TypeExpression::Ident(Ident::new("User", Span::call_site()))));
+    }
+
+    #[test]
+    fn test_parse_rename() {
+        let input = parse_quote! {
+            Rename(User, name => display_name, CamelCase, suffix = "_raw",)
+        };
+
+        let actual = parse2::<UtilityOperation>(input).unwrap();
+
+        let expected = UtilityOperation::Rename(
+            TypeExpression::Ident(Ident::new("User", Span::call_site())),
+            vec![parse_quote!(name => display_name), parse_quote!(CamelCase), parse_quote!(suffix = "_raw")]
+        );
+        assert_eq!(actual, expected);
+        assert_eq!(actual.to_string(), "Rename<User, name => display_name, PascalCase, suffix = \"_raw\">");
+    }
+
+    #[test]
+    fn test_parse_rename_without_rules() {
+        let input = parse_quote! {
+            Rename(User)
+        };
+
+        let actual = parse2::<UtilityOperation>(input);
+        assert_eq!(actual.err().unwrap().to_string(), "Expected a type followed by at least 1 rename rule");
+    }
+
+    #[test]
+    fn test_parse_wrap() {
+        let input = parse_quote! {
+            Wrap(State, Arc, config, addr_*)
+        };
+
+        let actual = parse2::<UtilityOperation>(input).unwrap();
+
+        let expected = UtilityOperation::Wrap(
+            TypeExpression::Ident(Ident::new("State", Span::call_site())),
+            parse_quote!(Arc),
+            vec![parse_quote!(config), parse_quote!(addr_*)]
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_wrap_path() {
+        let input = parse_quote! {
+            Unwrap(State, ::alloc::boxed::Box, config)
+        };
+
+        let actual = parse2::<UtilityOperation>(input).unwrap();
+
+        let expected = UtilityOperation::Unwrap(
+            TypeExpression::Ident(Ident::new("State", Span::call_site())),
+            parse_quote!(::alloc::boxed::Box),
+            vec![parse_quote!(config)]
+        );
+        assert_eq!(actual, expected);
+        assert_eq!(actual.to_string(), "Unwrap<State, ::alloc::boxed::Box, config>");
+
+        let input = parse_quote! {
+            Wrap(State, std::sync::Arc<u8>)
+        };
+
+        let actual = parse2::<UtilityOperation>(input);
+        assert_eq!(
+            actual.err().unwrap().to_string(),
+            "Expected the path of a wrapper type such as `Box` or `std::sync::Arc`");
+    }
+
+    #[test]
+    fn test_parse_unwrap_without_wrapper() {
+        let input = parse_quote! {
+            Unwrap(Boxed)
+        };
+
+        let actual = parse2::<UtilityOperation>(input);
+        assert_eq!(
+            actual.err().unwrap().to_string(),
+            "Expected a type followed by a wrapper type such as `Box`, but instead found 1 parameters");
+    }
+
+    #[test]
+    fn test_parse_map() {
+        let input = parse_quote! {
+            Map(User, Option<$T> => Vec<$T>, String => Cow<'a, str>, $T => Box<$T>)
+        };
+
+        let actual = parse2::<UtilityOperation>(input).unwrap();
+
+        assert_eq!(
+            actual.to_string(),
+            "Map<User, Option < $T > => Vec < $T >, String => Cow < 'a , str >, $T => Box < $T >>");
+    }
+
+    #[test]
+    fn test_parse_map_unbound_metavariable() {
+        let input = parse_quote! {
+            Map(T, Option<$T> => Vec<$U>)
+        };
+
+        let actual = parse2::<UtilityOperation>(input);
+
+        assert_eq!(actual.err().unwrap().to_string(), "`$U` is not bound by the pattern `Option < $T >`");
+    }
+
+    #[test]
+    fn test_parse_map_without_rules() {
+        let input = parse_quote! {
+            Map(User)
+        };
+
+        let actual = parse2::<UtilityOperation>(input);
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn test_parse_field_type() {
+        let input = parse_quote! {
+            FieldType(User, id)
+        };
+
+        let actual = parse2::<UtilityOperation>(input).unwrap();
+
+        let expected = UtilityOperation::FieldType(
+            TypeExpression::Ident(Ident::new("User", Span::call_site())),
+            Ident::new("id", Span::call_site())
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_field_type_with_fields() {
+        let input = parse_quote! {
+            FieldType(User, id, name)
+        };
+
+        let actual = parse2::<UtilityOperation>(input);
+        assert_eq!(actual.err().unwrap().to_string(), "Expected a type followed by a single field, but instead found 2 fields");
+    }
 }
 
 fn format_util_op(util_op: &UtilityOperation, f: &mut Formatter<'_>) -> std::fmt::Result {
     match util_op {
-        UtilityOperation::Required(ident) => {
+        UtilityOperation::Required(ident, selectors) if selectors.is_empty() => {
             write!(f, "Required<{}>", ident)
         },
-        UtilityOperation::Optional(ident) => {
+        UtilityOperation::Required(ident, selectors) => {
+            write!(f, "Required<{}, {}>", ident, join(selectors))
+        },
+        UtilityOperation::Optional(ident, selectors) if selectors.is_empty() => {
             write!(f, "Optional<{}>", ident)
+        },
+        UtilityOperation::Optional(ident, selectors) => {
+            write!(f, "Optional<{}, {}>", ident, join(selectors))
+        },
+        UtilityOperation::Pick(ident, fields) => {
+            write!(f, "Pick<{}, {}>", ident, join(fields))
+        },
+        UtilityOperation::Omit(ident, fields) => {
+            write!(f, "Omit<{}, {}>", ident, join(fields))
+        },
+        UtilityOperation::Merge(idents, conflict) => {
+            write!(f, "Merge<{}, conflict = {}>", join(idents), conflict)
+        },
+        UtilityOperation::KeyOf(ident) => {
+            write!(f, "KeyOf<{}>", ident)
+        },
+        UtilityOperation::FieldType(ident, field) => {
+            write!(f, "FieldType<{}, {}>", ident, field)
+        },
+        UtilityOperation::Rename(ident, rules) => {
+            write!(f, "Rename<{}, {}>", ident, join(rules))
+        },
+        UtilityOperation::Wrap(ident, wrapper, selectors) if selectors.is_empty() => {
+            write!(f, "Wrap<{}, {}>", ident, format_path(wrapper))
+        },
+        UtilityOperation::Wrap(ident, wrapper, selectors) => {
+            write!(f, "Wrap<{}, {}, {}>", ident, format_path(wrapper), join(selectors))
+        },
+        UtilityOperation::Unwrap(ident, wrapper, selectors) if selectors.is_empty() => {
+            write!(f, "Unwrap<{}, {}>", ident, format_path(wrapper))
+        },
+        UtilityOperation::Unwrap(ident, wrapper, selectors) => {
+            write!(f, "Unwrap<{}, {}, {}>", ident, format_path(wrapper), join(selectors))
+        },
+        UtilityOperation::Map(ident, rules) => {
+            write!(f, "Map<{}, {}>", ident, join(rules))
+        },
+        UtilityOperation::DeepOptional(ident) => {
+            write!(f, "DeepOptional<{}>", ident)
+        },
+        UtilityOperation::DeepRequired(ident) => {
+            write!(f, "DeepRequired<{}>", ident)
         }
     }
 }
 
+fn join<T: Display>(items: &[T]) -> String {
+    items.iter().map(|i| i.to_string()).collect::<Vec<String>>().join(", ")
+}
+
 impl Debug for UtilityOperation {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         format_util_op(self, f)
@@ -83,17 +740,38 @@ impl Display for UtilityOperation {
 impl PartialEq for UtilityOperation {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
-            (UtilityOperation::Required(ident1), UtilityOperation::Required(ident2)) => {
-                ident1 == ident2
+            (UtilityOperation::Required(ident1, selectors1), UtilityOperation::Required(ident2, selectors2)) => {
+                ident1 == ident2 && selectors1 == selectors2
+            },
+            (UtilityOperation::Optional(ident1, selectors1), UtilityOperation::Optional(ident2, selectors2)) => {
+                ident1 == ident2 && selectors1 == selectors2
+            },
+            (UtilityOperation::Pick(ident1, fields1), UtilityOperation::Pick(ident2, fields2)) => {
+                ident1 == ident2 && fields1 == fields2
             },
-            (UtilityOperation::Optional(ident1), UtilityOperation::Optional(ident2)) => {
-                ident1 == ident2
+            (UtilityOperation::Omit(ident1, fields1), UtilityOperation::Omit(ident2, fields2)) => {
+                ident1 == ident2 && fields1 == fields2
             },
+            (UtilityOperation::Merge(idents1, conflict1), UtilityOperation::Merge(idents2, conflict2)) => {
+                idents1 == idents2 && conflict1 == conflict2
+            },
+            (UtilityOperation::KeyOf(ident1), UtilityOperation::KeyOf(ident2)) => ident1 == ident2,
+            (UtilityOperation::FieldType(ident1, field1), UtilityOperation::FieldType(ident2, field2)) => {
+                ident1 == ident2 && field1 == field2
+            },
+            (UtilityOperation::Rename(ident1, rules1), UtilityOperation::Rename(ident2, rules2)) => {
+                ident1 == ident2 && rules1 == rules2
+            },
+            (UtilityOperation::Wrap(ident1, wrapper1, selectors1), UtilityOperation::Wrap(ident2, wrapper2, selectors2)) |
+            (UtilityOperation::Unwrap(ident1, wrapper1, selectors1), UtilityOperation::Unwrap(ident2, wrapper2, selectors2)) => {
+                ident1 == ident2 && format_path(wrapper1) == format_path(wrapper2) && selectors1 == selectors2
+            },
+            (UtilityOperation::Map(ident1, rules1), UtilityOperation::Map(ident2, rules2)) => {
+                ident1 == ident2 && rules1 == rules2
+            },
+            (UtilityOperation::DeepOptional(ident1), UtilityOperation::DeepOptional(ident2)) |
+            (UtilityOperation::DeepRequired(ident1), UtilityOperation::DeepRequired(ident2)) => ident1 == ident2,
             _ => false
         }
     }
-
-    fn ne(&self, other: &Self) -> bool {
-        !self.eq(other)
-    }
-}
\ No newline at end of file
+}
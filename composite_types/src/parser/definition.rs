@@ -0,0 +1,193 @@
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::ToTokens;
+use syn::{Attribute, Field, Fields, Generics, Item, ItemEnum, ItemStruct, ItemType, Visibility};
+use syn::token::{Brace, Paren};
+use syn::parse::{Parse, ParseStream};
+
+/// A type definition, either written inside the macro or produced by an operation. Operations
+/// apply to the fields of a struct, or to the fields of every variant of an enum. Type aliases
+/// have no fields, and are only produced by `FieldType`.
+#[derive(Clone)]
+pub enum Definition {
+    Struct(ItemStruct),
+    Enum(ItemEnum),
+    Type(ItemType),
+}
+
+impl Definition {
+    pub fn ident(&self) -> &Ident {
+        match self {
+            Definition::Struct(structure) => &structure.ident,
+            Definition::Enum(enumeration) => &enumeration.ident,
+            Definition::Type(alias) => &alias.ident
+        }
+    }
+
+    pub fn set_ident(&mut self, ident: Ident) {
+        match self {
+            Definition::Struct(structure) => structure.ident = ident,
+            Definition::Enum(enumeration) => enumeration.ident = ident,
+            Definition::Type(alias) => alias.ident = ident
+        }
+    }
+
+    /// Renames the definition to `{ident}{suffix}`, e.g. `UserOptional`, keeping the span.
+    pub fn append_to_ident(&mut self, suffix: &str) {
+        let ident = self.ident();
+        self.set_ident(Ident::new(&format!("{}{}", ident, suffix), ident.span()));
+    }
+
+    pub fn set_vis(&mut self, vis: Visibility) {
+        match self {
+            Definition::Struct(structure) => structure.vis = vis,
+            Definition::Enum(enumeration) => enumeration.vis = vis,
+            Definition::Type(alias) => alias.vis = vis
+        }
+    }
+
+    pub fn attrs_mut(&mut self) -> &mut Vec<Attribute> {
+        match self {
+            Definition::Struct(structure) => &mut structure.attrs,
+            Definition::Enum(enumeration) => &mut enumeration.attrs,
+            Definition::Type(alias) => &mut alias.attrs
+        }
+    }
+
+    /// Sets the span of the `struct`, `enum` or `type` keyword, and of the delimiters around the
+    /// body, so the definition as a whole points at `span` rather than the type it was copied from.
+    pub fn set_kind_span(&mut self, span: Span) {
+        match self {
+            Definition::Struct(structure) => {
+                structure.struct_token.span = span;
+                match &mut structure.fields {
+                    Fields::Named(named) => named.brace_token = Brace(span),
+                    Fields::Unnamed(unnamed) => unnamed.paren_token = Paren(span),
+                    Fields::Unit => {}
+                }
+                if let Some(semi_token) = &mut structure.semi_token {
+                    semi_token.spans = [span];
+                }
+            },
+            Definition::Enum(enumeration) => {
+                enumeration.enum_token.span = span;
+                enumeration.brace_token = Brace(span);
+            },
+            Definition::Type(alias) => {
+                alias.type_token.span = span;
+                alias.semi_token.spans = [span];
+            }
+        }
+    }
+
+    pub fn generics(&self) -> &Generics {
+        match self {
+            Definition::Struct(structure) => &structure.generics,
+            Definition::Enum(enumeration) => &enumeration.generics,
+            Definition::Type(alias) => &alias.generics
+        }
+    }
+
+    pub fn generics_mut(&mut self) -> &mut Generics {
+        match self {
+            Definition::Struct(structure) => &mut structure.generics,
+            Definition::Enum(enumeration) => &mut enumeration.generics,
+            Definition::Type(alias) => &mut alias.generics
+        }
+    }
+
+    /// Fields of the struct, or of every variant of the enum in declaration order.
+    pub fn fields(&self) -> Box<dyn Iterator<Item = &Field> + '_> {
+        match self {
+            Definition::Struct(structure) => Box::new(structure.fields.iter()),
+            Definition::Enum(enumeration) => Box::new(enumeration.variants.iter()
+                .flat_map(|variant| variant.fields.iter())),
+            Definition::Type(_) => Box::new(std::iter::empty())
+        }
+    }
+
+    /// Mutable fields of the struct, or of every variant of the enum in declaration order.
+    pub fn fields_mut(&mut self) -> Box<dyn Iterator<Item = &mut Field> + '_> {
+        match self {
+            Definition::Struct(structure) => Box::new(structure.fields.iter_mut()),
+            Definition::Enum(enumeration) => Box::new(enumeration.variants.iter_mut()
+                .flat_map(|variant| variant.fields.iter_mut())),
+            Definition::Type(_) => Box::new(std::iter::empty())
+        }
+    }
+
+    /// Name of the kind of definition, as used in error messages.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Definition::Struct(_) => "struct",
+            Definition::Enum(_) => "enum",
+            Definition::Type(_) => "type"
+        }
+    }
+}
+
+impl Parse for Definition {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        match input.parse::<Item>()? {
+            Item::Struct(structure) => Ok(Definition::Struct(structure)),
+            Item::Enum(enumeration) => Ok(Definition::Enum(enumeration)),
+            item => Err(syn::Error::new_spanned(item, "Expected a struct or enum definition"))
+        }
+    }
+}
+
+impl From<ItemStruct> for Definition {
+    fn from(structure: ItemStruct) -> Self {
+        Definition::Struct(structure)
+    }
+}
+
+impl From<ItemEnum> for Definition {
+    fn from(enumeration: ItemEnum) -> Self {
+        Definition::Enum(enumeration)
+    }
+}
+
+impl ToTokens for Definition {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            Definition::Struct(structure) => structure.to_tokens(tokens),
+            Definition::Enum(enumeration) => enumeration.to_tokens(tokens),
+            Definition::Type(alias) => alias.to_tokens(tokens)
+        }
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_enum_fields() {
+        let input: ItemEnum = parse_quote! {
+            enum Shape {
+                Circle { radius: f64 },
+                Square(f64),
+                Empty = 3
+            }
+        };
+        let definition = Definition::from(input);
+
+        let actual = definition.fields()
+            .map(|field| field.ty.to_token_stream().to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(actual, vec!["f64", "f64"]);
+    }
+
+    #[test]
+    fn test_append_to_ident() {
+        let input: ItemStruct = parse_quote! {
+            struct User;
+        };
+        let mut definition = Definition::from(input);
+
+        definition.append_to_ident("Optional");
+        assert_eq!(definition.ident().to_string(), "UserOptional");
+    }
+}
@@ -1,2 +1,4 @@
 pub mod composite_struct;
-pub mod type_input;
\ No newline at end of file
+pub mod definition;
+pub mod type_input;
+pub mod settings;
@@ -0,0 +1,157 @@
+use std::fmt::{Debug, Formatter};
+use proc_macro2::Span;
+use quote::ToTokens;
+use syn::parse::ParseStream;
+use syn::punctuated::Punctuated;
+use syn::{parenthesized, Attribute, Path, Token};
+use syn::spanned::Spanned;
+
+/// Options which apply to every item of a macro invocation, given as inner attributes before the
+/// items, e.g. `#![compose(nullable(Maybe, Nullable))]`.
+#[derive(Default, Clone)]
+pub struct Settings {
+    /// Generic types which are treated like `Option` by `Optional` and `Required`. Their first
+    /// type argument is the type they wrap.
+    pub nullable: Vec<Path>,
+}
+
+impl Settings {
+    /// Parses any number of `#![compose(...)]` attributes. Invocations without them use the
+    /// default settings.
+    pub fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut settings = Settings::default();
+        for attribute in input.call(Attribute::parse_inner)? {
+            if !attribute.path().is_ident("compose") {
+                return Err(syn::Error::new_spanned(attribute.path(), "Expected `#![compose(...)]`"))
+            }
+            attribute.parse_nested_meta(|meta| {
+                if meta.path.is_ident("nullable") {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    let wrappers = Punctuated::<Path, Token![,]>::parse_terminated_with(&content, Path::parse_mod_style)?;
+                    settings.nullable.extend(wrappers);
+                    return Ok(())
+                }
+                Err(meta.error("Expected `nullable(Wrapper, ...)`"))
+            })?;
+        }
+        Ok(settings)
+    }
+}
+
+/// Options of a single declaration, given as `#[compose(...)]` attributes on it, e.g.
+/// `#[compose(internal)] struct Base { ... }`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ItemSettings {
+    /// The type can be used by operations, but isn't emitted.
+    pub internal: bool,
+    /// A copy like `struct Savings = BankAccount;` gets `From` conversions in both directions.
+    /// Errors about the conversions are reported at the span of the option.
+    pub from: Option<Span>,
+}
+
+impl ItemSettings {
+    /// Removes the `#[compose(...)]` attributes from `attrs` and parses them, keeping any other
+    /// attributes.
+    pub fn take(attrs: &mut Vec<Attribute>) -> syn::Result<Self> {
+        let mut settings = ItemSettings::default();
+        let mut result = Ok(());
+        attrs.retain(|attribute| {
+            if !attribute.path().is_ident("compose") {
+                return true
+            }
+            if result.is_ok() {
+                result = attribute.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("internal") {
+                        settings.internal = true;
+                        return Ok(())
+                    }
+                    if meta.path.is_ident("from") {
+                        settings.from = Some(meta.path.span());
+                        return Ok(())
+                    }
+                    Err(meta.error("Expected `internal` or `from`"))
+                });
+            }
+            false
+        });
+        result.map(|_| settings)
+    }
+}
+
+// implement unit tests
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod tests {
+    use super::*;
+    use syn::parse::Parser;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_parse_settings() {
+        let input = parse_quote! {
+            #![compose(nullable(Maybe, types::Nullable))]
+        };
+
+        let actual = Settings::parse.parse2(input).unwrap();
+
+        let expected = Settings { nullable: vec![parse_quote!(Maybe), parse_quote!(types::Nullable)] };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_settings_unknown_option() {
+        let input = parse_quote! {
+            #![compose(strict)]
+        };
+
+        let actual = Settings::parse.parse2(input);
+        assert_eq!(actual.err().unwrap().to_string(), "Expected `nullable(Wrapper, ...)`");
+    }
+
+    #[test]
+    fn test_take_item_settings() {
+        let mut attrs: Vec<Attribute> = vec![parse_quote!(#[derive(Debug)]), parse_quote!(#[compose(internal, from)])];
+
+        let actual = ItemSettings::take(&mut attrs).unwrap();
+
+        assert_eq!(actual, ItemSettings { internal: true, from: Some(Span::call_site()) });
+        assert_eq!(attrs.len(), 1);
+        assert!(attrs[0].path().is_ident("derive"));
+
+        let mut attrs: Vec<Attribute> = vec![parse_quote!(#[compose(hidden)])];
+        let actual = ItemSettings::take(&mut attrs);
+        assert_eq!(actual.err().unwrap().to_string(), "Expected `internal` or `from`");
+    }
+
+    #[test]
+    fn test_parse_settings_other_attribute() {
+        let input = parse_quote! {
+            #![allow(dead_code)]
+        };
+
+        let actual = Settings::parse.parse2(input);
+        assert_eq!(actual.err().unwrap().to_string(), "Expected `#![compose(...)]`");
+    }
+}
+
+impl Debug for Settings {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let nullable = self.nullable.iter()
+            .map(|path| path.to_token_stream().to_string())
+            .collect::<Vec<String>>();
+        write!(f, "Settings {{ nullable: [{}] }}", nullable.join(", "))
+    }
+}
+
+impl PartialEq for Settings {
+    fn eq(&self, other: &Self) -> bool {
+        format!("{:?}", self) == format!("{:?}", other)
+    }
+}
+
+impl PartialEq for ItemSettings {
+    fn eq(&self, other: &Self) -> bool {
+        self.internal == other.internal && self.from.is_some() == other.from.is_some()
+    }
+}
@@ -1,54 +1,116 @@
 use std::fmt::{Debug, Formatter};
 use quote::ToTokens;
-use syn::ItemStruct;
 use syn::parse::{Parse, ParseStream};
-use crate::parser::composite_struct::CompositeStruct;
+use proc_macro2::Ident;
+use syn::{braced, Attribute, Item, ItemMod, Token, Visibility};
+use crate::parser::composite_struct::{CompositeOperation, CompositeStruct};
+use crate::parser::definition::Definition;
+use crate::parser::settings::{ItemSettings, Settings};
 
 #[derive(Debug, PartialEq)]
 pub struct TypeInput {
+    pub settings: Settings,
     pub items: Vec<InputType>,
 }
 
 pub enum InputType {
     CompositeStruct(CompositeStruct),
-    Definition(ItemStruct)
+    /// A type written out in the macro, without its `#[compose(...)]` attributes.
+    Definition(Definition, ItemSettings),
+    /// `mod models { ... }` groups items into a module. The module is kept with empty content,
+    /// which is parsed into items of its own.
+    Module(ItemMod, Vec<InputType>),
+    /// Any other item, e.g. `use`, `impl` or `fn`, which is emitted unchanged.
+    Item(Item)
 }
 
 impl Parse for InputType {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        if let Ok(composite) = input.fork().parse::<CompositeStruct>() {
-            let _ = input.parse::<CompositeStruct>();
-            return Ok(InputType::CompositeStruct(composite));
-        } else if let Ok(definition) = input.fork().parse::<ItemStruct>() {
-            let _ = input.parse::<ItemStruct>();
-            return Ok(InputType::Definition(definition));
-        } else {
-            Err(input.error("Expected composite struct or type definition"))
+        if is_module(input) {
+            let attrs = input.call(Attribute::parse_outer)?;
+            let vis = input.parse()?;
+            let mod_token = input.parse()?;
+            let ident = input.parse()?;
+            let content;
+            let brace = braced!(content in input);
+            let items = parse_items(&content)?;
+            let module = ItemMod { attrs, vis, unsafety: None, mod_token, ident, content: Some((brace, Vec::new())), semi: None };
+            return Ok(InputType::Module(module, items))
+        }
+        if is_composite_struct(input) {
+            return Ok(InputType::CompositeStruct(input.parse()?));
+        }
+        match input.parse::<Item>()? {
+            Item::Struct(mut structure) => {
+                let settings = ItemSettings::take(&mut structure.attrs)?;
+                Ok(InputType::Definition(structure.into(), settings))
+            },
+            Item::Enum(mut enumeration) => {
+                let settings = ItemSettings::take(&mut enumeration.attrs)?;
+                Ok(InputType::Definition(enumeration.into(), settings))
+            },
+            item => Ok(InputType::Item(item))
         }
     }
 }
 
 impl Parse for TypeInput {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        let mut items = Vec::new();
+        let settings = Settings::parse(input)?;
+        let items = parse_items(input)?;
+        Ok(TypeInput { settings, items })
+    }
+}
 
-        // Continue parsing while there's more content in the input.
-        while !input.is_empty() {
-            items.push(input.parse::<InputType>()?);
-        }
+fn parse_items(input: ParseStream) -> syn::Result<Vec<InputType>> {
+    let mut items = Vec::new();
 
-        Ok(TypeInput { items })
+    // Continue parsing while there's more content in the input.
+    while !input.is_empty() {
+        items.push(input.parse::<InputType>()?);
     }
+    Ok(items)
+}
+
+fn is_module(input: ParseStream) -> bool {
+    let fork = input.fork();
+    fork.call(Attribute::parse_outer).is_ok() && fork.parse::<Visibility>().is_ok()
+        && fork.parse::<Token![mod]>().is_ok() && fork.parse::<Ident>().is_ok() && fork.peek(syn::token::Brace)
+}
+
+/// Whether the input starts like `struct Name = ...`, so errors are reported for the composition
+/// instead of for a Rust item. `type Name = ...` is also a Rust type alias, e.g.
+/// `type Pair = (u8, u8);`, so it is only a composition if what follows `=` is an operation, or if
+/// it isn't a valid type alias either. Plain aliases like `type Account = BankAccount;` are
+/// emitted as written unless they have `#[compose(...)]` attributes.
+fn is_composite_struct(input: ParseStream) -> bool {
+    let fork = input.fork();
+    let attrs = fork.call(Attribute::parse_outer).unwrap_or_default();
+    let _ = fork.parse::<Visibility>();
+    let is_type = fork.peek(Token![type]);
+    let is_kind = fork.parse::<Token![struct]>().is_ok()
+        || fork.parse::<Token![enum]>().is_ok()
+        || fork.parse::<Token![type]>().is_ok();
+    if !is_kind || fork.parse::<Ident>().is_err() || fork.parse::<Token![=]>().is_err() {
+        return false
+    }
+    let has_settings = attrs.iter().any(|attribute| attribute.path().is_ident("compose"));
+    !is_type
+        || fork.parse::<CompositeOperation>().is_ok_and(|operation| (fork.is_empty() || fork.peek(Token![;]))
+            && (operation.alias_target().is_none() || has_settings))
+        || input.fork().parse::<Item>().is_err()
 }
 
 // implement unit tests
 #[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod tests {
     use proc_macro2::{Ident, Span};
     use super::*;
     use syn::{parse2, parse_quote};
     use crate::parser::composite_struct::CompositeOperation;
     use crate::parser::composite_struct::utility_operations::UtilityOperation;
+    use crate::parser::composite_struct::type_expression::TypeExpression;
 
     #[test]
     fn test_parse_type_input() {
@@ -60,28 +122,167 @@ mod tests {
         let actual = parse2::<TypeInput>(input).unwrap();
 
         let expected = TypeInput {
+            settings: Settings::default(),
             items: vec![
                 InputType::CompositeStruct(CompositeStruct::new(
                     Ident::new("MyStruct", Span::call_site()),
                     CompositeOperation::UtilityOp(
-                        UtilityOperation::Required(Ident::new("Ident", Span::call_site())))
+                        UtilityOperation::Required(TypeExpression::Ident(Ident::new("Ident", Span::call_site())), vec![]))
                 )),
                 InputType::CompositeStruct(CompositeStruct::new(
                     Ident::new("MyStruct2", Span::call_site()),
                     CompositeOperation::UtilityOp(
-                        UtilityOperation::Required(Ident::new("Ident", Span::call_site())))
+                        UtilityOperation::Required(TypeExpression::Ident(Ident::new("Ident", Span::call_site())), vec![]))
                 )),
             ]
         };
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_parse_type_input_with_module() {
+        let input = parse_quote! {
+            pub mod models {
+                pub struct User {
+                    id: u64
+                }
+                mod admin {
+                    pub struct Admin = super::User;
+                }
+            }
+            struct Patch = Optional(models::User);
+        };
+
+        let actual = parse2::<TypeInput>(input).unwrap();
+
+        assert_eq!(actual.items.len(), 2);
+        let InputType::Module(module, items) = &actual.items[0] else { panic!("Expected a module") };
+        assert_eq!(module.ident, "models");
+        assert_eq!(items.len(), 2);
+        assert!(matches!(&items[1], InputType::Module(module, items) if module.ident == "admin" && items.len() == 1));
+    }
+
+    #[test]
+    fn test_parse_type_input_with_items() {
+        let input = parse_quote! {
+            use std::fmt::Debug;
+            struct User {
+                id: u64
+            }
+            impl User {
+                fn new(id: u64) -> Self {
+                    User { id }
+                }
+            }
+            const ADMIN: u64 = 0;
+            type Bytes = Vec<u8>;
+            type UserId = FieldType(User, id);
+            mod cache;
+        };
+
+        let actual = parse2::<TypeInput>(input).unwrap();
+
+        let kinds = actual.items.iter().map(|item| match item {
+            InputType::CompositeStruct(_) => "composite",
+            InputType::Definition(..) => "definition",
+            InputType::Module(..) => "module",
+            InputType::Item(_) => "item"
+        }).collect::<Vec<&str>>();
+        assert_eq!(kinds, vec!["item", "definition", "item", "item", "item", "composite", "item"]);
+        assert_eq!(actual.items[4], InputType::Item(parse_quote!(type Bytes = Vec<u8>;)));
+    }
+
+    #[test]
+    fn test_parse_type_input_with_type_aliases() {
+        let input = parse_quote! {
+            type Pair = (u8, u8);
+            type Name = &'static str;
+            pub type Callback = fn(i32) -> i32;
+            type Digest = [u8; 32];
+            type Page<T> = Vec<T>;
+            type Key = <User as Keyed>::Key;
+            type Patch = Optional(User);
+            type Account = models::BankAccount;
+            #[compose(internal)]
+            type Base = BankAccount;
+        };
+
+        let actual = parse2::<TypeInput>(input).unwrap();
+
+        assert_eq!(actual.items[0], InputType::Item(parse_quote!(type Pair = (u8, u8);)));
+        assert_eq!(actual.items[1], InputType::Item(parse_quote!(type Name = &'static str;)));
+        assert_eq!(actual.items[2], InputType::Item(parse_quote!(pub type Callback = fn(i32) -> i32;)));
+        assert_eq!(actual.items[3], InputType::Item(parse_quote!(type Digest = [u8; 32];)));
+        assert_eq!(actual.items[4], InputType::Item(parse_quote!(type Page<T> = Vec<T>;)));
+        assert_eq!(actual.items[5], InputType::Item(parse_quote!(type Key = <User as Keyed>::Key;)));
+        assert!(matches!(actual.items[6], InputType::CompositeStruct(_)));
+        assert_eq!(actual.items[7], InputType::Item(parse_quote!(type Account = models::BankAccount;)));
+        assert!(matches!(&actual.items[8], InputType::CompositeStruct(composite) if composite.settings.internal));
+    }
+
+    #[test]
+    fn test_parse_type_input_with_internal_types() {
+        let input = parse_quote! {
+            #[compose(internal)]
+            #[derive(Debug)]
+            struct Base {
+                id: u64
+            }
+            #[compose(internal)]
+            struct BasePatch = Optional(Base);
+        };
+
+        let actual = parse2::<TypeInput>(input).unwrap();
+
+        let expected = TypeInput {
+            settings: Settings::default(),
+            items: vec![
+                InputType::Definition(parse_quote! {
+                    #[derive(Debug)]
+                    struct Base {
+                        id: u64
+                    }
+                }, ItemSettings { internal: true, from: None }),
+                InputType::CompositeStruct(parse_quote!(#[compose(internal)] struct BasePatch = Optional(Base);)),
+            ]
+        };
+        assert_eq!(actual, expected);
+        let InputType::CompositeStruct(composite) = &actual.items[1] else { panic!("Expected a composition") };
+        assert!(composite.settings.internal);
+    }
+
+    #[test]
+    fn test_parse_type_input_with_invalid_composition() {
+        let input = parse_quote! {
+            struct Patch = Optional(User, 42);
+        };
+
+        let actual = parse2::<TypeInput>(input);
+
+        assert_eq!(actual.err().unwrap().to_string(), "expected identifier");
+    }
+
+    #[test]
+    fn test_parse_type_input_with_settings() {
+        let input = parse_quote! {
+            #![compose(nullable(Maybe))]
+            struct MyStruct = Required(Ident);
+        };
+
+        let actual = parse2::<TypeInput>(input).unwrap();
+
+        assert_eq!(actual.settings, Settings { nullable: vec![parse_quote!(Maybe)] });
+        assert_eq!(actual.items.len(), 1);
+    }
 }
 
 impl Debug for InputType {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             InputType::CompositeStruct(cs) => write!(f, "{:?}", cs),
-            InputType::Definition(def) => write!(f, "{:?}", def.to_token_stream()),
+            InputType::Definition(def, settings) => write!(f, "{:?} {:?}", def.to_token_stream(), settings),
+            InputType::Module(module, items) => write!(f, "mod {} {:?}", module.ident, items),
+            InputType::Item(item) => write!(f, "{:?}", item.to_token_stream()),
         }
     }
 }
@@ -90,13 +291,13 @@ impl PartialEq for InputType {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (InputType::CompositeStruct(cs1), InputType::CompositeStruct(cs2)) => cs1 == cs2,
-            (InputType::Definition(def1), InputType::Definition(def2)) =>
-                def1.to_token_stream().to_string() == def2.to_token_stream().to_string(),
+            (InputType::Definition(def1, settings1), InputType::Definition(def2, settings2)) =>
+                def1.to_token_stream().to_string() == def2.to_token_stream().to_string() && settings1 == settings2,
+            (InputType::Module(module1, items1), InputType::Module(module2, items2)) =>
+                module1.to_token_stream().to_string() == module2.to_token_stream().to_string() && items1 == items2,
+            (InputType::Item(item1), InputType::Item(item2)) =>
+                item1.to_token_stream().to_string() == item2.to_token_stream().to_string(),
             _ => false,
         }
     }
-
-    fn ne(&self, other: &Self) -> bool {
-        !self.eq(other)
-    }
 }
\ No newline at end of file
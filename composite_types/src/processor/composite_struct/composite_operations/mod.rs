@@ -1,22 +1,62 @@
 use proc_macro2::Ident;
 use crate::parser::composite_struct::CompositeOperation;
-use crate::processor::errors::generate_not_found_error;
+use crate::parser::composite_struct::utility_operations::UtilityOperation;
 use crate::processor::State;
-use syn::{ItemStruct, Result};
-use crate::processor::composite_struct::composite_operations::type_alias::process_type_alias;
+use syn::Result;
+use crate::parser::definition::Definition;
+use crate::processor::composite_struct::composite_operations::type_alias::{process_generic_alias, process_path_alias, process_type_alias};
+use crate::processor::composite_struct::composite_operations::type_expression::process_type_expression;
+use crate::processor::composite_struct::composite_operations::union::process_union;
+use crate::processor::composite_struct::composite_operations::utility_operations::deep::{DeepOperation, process_deep};
+use crate::processor::composite_struct::composite_operations::utility_operations::key_of::{generate_key_impls, process_key_of};
 use crate::processor::composite_struct::composite_operations::utility_operations::process_utility_operator;
+use crate::processor::composite_struct::composite_operations::utility_operations::rename::{generate_rename_conversions, process_rename};
 
 mod utility_operations;
-mod type_alias;
+pub mod type_alias;
+mod type_expression;
+mod union;
 
-pub fn process_composite_operation(mut state: &mut State, assignment_ident: &Ident,
-                               composite_operation: &CompositeOperation) -> Result<ItemStruct>{
+pub fn process_composite_operation(state: &mut State, assignment_ident: &Ident,
+                               composite_operation: &CompositeOperation) -> Result<Definition> {
     match composite_operation {
         CompositeOperation::TypeAlias(original) => {
-            process_type_alias(&mut state, assignment_ident, &original)
+            process_type_alias(state, assignment_ident, original)
+        },
+        CompositeOperation::GenericAlias(original, arguments) => {
+            process_generic_alias(state, original, arguments)
+        },
+        CompositeOperation::PathAlias(path) => {
+            process_path_alias(state, path)
+        },
+        // The key enum can only implement `Display` and `FromStr` once its name is known
+        CompositeOperation::UtilityOp(UtilityOperation::KeyOf(expression)) => {
+            let definition = process_type_expression(state, expression)?;
+            let mut keys = process_key_of(&definition)?;
+            keys.set_ident(assignment_ident.clone());
+            state.impls.insert(state.key(assignment_ident), generate_key_impls(&keys, &definition)?);
+            Ok(keys)
+        },
+        // Conversions from and to the renamed struct also need its name
+        CompositeOperation::UtilityOp(UtilityOperation::Rename(expression, rules)) => {
+            let definition = process_type_expression(state, expression)?;
+            let mut renamed = process_rename(&definition, rules)?;
+            renamed.set_ident(assignment_ident.clone());
+            state.impls.insert(state.key(assignment_ident), generate_rename_conversions(expression, &definition, &renamed));
+            Ok(renamed)
+        },
+        // Contained types which refer back to the type need its name
+        CompositeOperation::UtilityOp(UtilityOperation::DeepOptional(expression)) => {
+            process_deep(state, expression, DeepOperation::Optional, Some(assignment_ident))
+        },
+        CompositeOperation::UtilityOp(UtilityOperation::DeepRequired(expression)) => {
+            process_deep(state, expression, DeepOperation::Required, Some(assignment_ident))
         },
         CompositeOperation::UtilityOp(uo) => {
-            process_utility_operator(&mut state, &uo)
+            process_utility_operator(state, uo)
+        },
+        CompositeOperation::Union(members) => {
+            process_union(state, assignment_ident, members)
         }
     }
 }
\ No newline at end of file
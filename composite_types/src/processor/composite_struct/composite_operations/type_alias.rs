@@ -1,17 +1,146 @@
-use proc_macro2::Ident;
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use crate::parser::composite_struct::type_expression::TypeExpression;
 use crate::processor::errors::generate_not_found_error;
 use crate::processor::State;
-use syn::{ItemStruct, Result};
+use syn::{parse_quote, AngleBracketedGenericArguments, Fields, ItemImpl, Path, PathArguments, Result, Type};
+use crate::parser::definition::Definition;
+use crate::processor::composite_struct::composite_operations::utility_operations::generics::instantiate_generics;
+use crate::processor::composite_struct::composite_operations::utility_operations::rename::conversion_source;
 
-pub fn process_type_alias(state: &mut State, alias: &Ident, composite: &Ident) -> Result<ItemStruct> {
-    let composite_string = composite.to_string();
-    let composite = state.variables.get(composite_string.as_str());
-    return match composite {
+pub fn process_type_alias(state: &mut State, alias: &Ident, composite: &Ident) -> Result<Definition> {
+    let composite = state.lookup(&state.key(composite));
+    match composite {
         None => generate_not_found_error(alias),
         Some(structure) => Ok(structure.clone())
     }
 }
 
+/// Instantiates the generic type `composite` with `arguments`, e.g. `struct UserPage = Page<User>;`.
+pub fn process_generic_alias(state: &mut State, composite: &Ident,
+                             arguments: &AngleBracketedGenericArguments) -> Result<Definition> {
+    match state.lookup(&state.key(composite)) {
+        None => generate_not_found_error(composite),
+        Some(structure) => instantiate_generics(structure, arguments)
+    }
+}
+
+/// Copies the type `path` refers to, e.g. `struct Admin = models::User;`, instantiating it if the
+/// last segment has generic arguments.
+pub fn process_path_alias(state: &mut State, path: &Path) -> Result<Definition> {
+    let structure = state.lookup(&state.resolve(path)?).unwrap();
+    match &path.segments.last().unwrap().arguments {
+        PathArguments::AngleBracketed(arguments) => instantiate_generics(structure, arguments),
+        _ => Ok(structure.clone())
+    }
+}
+
+/// Declares `type Savings = BankAccount;` as a Rust type alias instead of a copy, so values of
+/// both types are interchangeable. Plain aliases are emitted as written, so this is only used for
+/// aliases with `#[compose(...)]` attributes.
+pub fn process_transparent_alias(state: &mut State, alias: &Ident, target: &TypeExpression) -> Result<Definition> {
+    let ty: Type = match target {
+        TypeExpression::Ident(ident) => parse_quote!(#ident),
+        TypeExpression::Generic(ident, arguments) => parse_quote!(#ident #arguments),
+        TypeExpression::Path(path) => parse_quote!(#path),
+        TypeExpression::UtilityOp(_) => return Err(syn::Error::new(target.span(), "Expected a type"))
+    };
+    register_alias(state, alias, target)?;
+    Ok(Definition::Type(parse_quote!(type #alias = #ty;)))
+}
+
+/// Lets operations applied to `alias` see the definition `target` names, if that type is declared
+/// in the macro. Aliases of other types, e.g. `Vec<u8>`, are left alone.
+pub fn register_alias(state: &mut State, alias: &Ident, target: &TypeExpression) -> Result<()> {
+    let definition = match target {
+        TypeExpression::Ident(ident) => state.lookup(&state.key(ident)).cloned(),
+        TypeExpression::Generic(ident, arguments) => state.lookup(&state.key(ident))
+            .map(|definition| instantiate_generics(definition, arguments))
+            .transpose()?,
+        TypeExpression::Path(path) => match state.resolve(path) {
+            Ok(_) => Some(process_path_alias(state, path)?),
+            Err(_) => None
+        },
+        TypeExpression::UtilityOp(_) => None
+    };
+    if let Some(definition) = definition {
+        state.aliases.insert(state.key(alias), definition);
+    }
+    Ok(())
+}
+
+/// `From` conversions in both directions between the type named by `target` and `copy`, which was
+/// copied from it by `#[compose(from)] struct Savings = BankAccount;`.
+pub fn generate_copy_conversions(target: &TypeExpression, copy: &Definition) -> Vec<ItemImpl> {
+    let Some((source, constructor)) = conversion_source(target, copy) else { return Vec::new() };
+    if let Definition::Type(_) = copy {
+        return Vec::new()
+    }
+    let name = copy.ident();
+    let (impl_generics, type_generics, where_clause) = copy.generics().split_for_impl();
+    let into_copy = convert(copy, &constructor, &parse_quote!(#name));
+    let into_source = convert(copy, &parse_quote!(#name), &constructor);
+    vec![
+        parse_quote! {
+            impl #impl_generics ::core::convert::From<#source> for #name #type_generics #where_clause {
+                fn from(value: #source) -> Self {
+                    #into_copy
+                }
+            }
+        },
+        parse_quote! {
+            impl #impl_generics ::core::convert::From<#name #type_generics> for #source #where_clause {
+                fn from(value: #name #type_generics) -> Self {
+                    #into_source
+                }
+            }
+        },
+    ]
+}
+
+/// Moves the fields of `value`, of the type constructed with `from`, into the type constructed
+/// with `to`. Both types have the fields of `definition`.
+fn convert(definition: &Definition, from: &Path, to: &Path) -> TokenStream {
+    match definition {
+        Definition::Struct(structure) => {
+            let fields = bindings(&structure.fields);
+            quote! {
+                let #from #fields = value;
+                #to #fields
+            }
+        },
+        Definition::Enum(enumeration) => {
+            let arms = enumeration.variants.iter().map(|variant| {
+                let ident = &variant.ident;
+                let fields = bindings(&variant.fields);
+                quote!(#from::#ident #fields => #to::#ident #fields)
+            });
+            quote! {
+                match value {
+                    #(#arms),*
+                }
+            }
+        },
+        Definition::Type(_) => quote!(value)
+    }
+}
+
+/// Binds every field by name, which reads the same as a pattern and as an expression, e.g.
+/// `{ id, name }` or `(field_0, field_1)`.
+fn bindings(fields: &Fields) -> TokenStream {
+    match fields {
+        Fields::Named(named) => {
+            let idents = named.named.iter().map(|field| &field.ident);
+            quote!({ #(#idents),* })
+        },
+        Fields::Unnamed(unnamed) => {
+            let idents = (0..unnamed.unnamed.len()).map(|index| format_ident!("field_{}", index));
+            quote!(( #(#idents),* ))
+        },
+        Fields::Unit => TokenStream::new()
+    }
+}
+
 // unit tests
 #[cfg(test)]
 mod tests {
@@ -25,7 +154,7 @@ mod tests {
         let input: Ident = parse_quote! { Test };
 
         let alias: Ident = parse_quote!(NewStruct);
-        let structure: ItemStruct = parse_quote! {
+        let structure: Definition = parse_quote! {
             struct Test {
                 field: FieldType
             }
@@ -57,4 +186,87 @@ mod tests {
         let actual = process_type_alias(&mut state, &alias, &input);
         assert!(actual.is_err());
     }
+
+    #[test]
+    fn test_transparent_alias() {
+        let mut state = State::new();
+        state.insert("Page".to_string(), parse_quote!(struct Page<T> { items: Vec<T> }));
+
+        let actual = process_transparent_alias(&mut state, &parse_quote!(UserPage), &parse_quote!(Page<User>)).unwrap();
+        assert_eq!(actual.to_token_stream().to_string(), "type UserPage = Page < User > ;");
+        assert_eq!(
+            state.lookup("UserPage").unwrap().to_token_stream().to_string(),
+            "struct Page { items : Vec < User > }");
+
+        let actual = process_transparent_alias(&mut state, &parse_quote!(Bytes), &parse_quote!(Vec<u8>)).unwrap();
+        assert_eq!(actual.to_token_stream().to_string(), "type Bytes = Vec < u8 > ;");
+        assert!(state.lookup("Bytes").is_none());
+    }
+
+    #[test]
+    fn test_copy_conversions() {
+        let copy: Definition = parse_quote! {
+            enum Shape<T> {
+                Circle { radius: T },
+                Square(T),
+                Empty
+            }
+        };
+
+        let actual = generate_copy_conversions(&parse_quote!(Figure), &copy);
+
+        let expected: Vec<ItemImpl> = vec![
+            parse_quote! {
+                impl<T> ::core::convert::From<Figure<T> > for Shape<T> {
+                    fn from(value: Figure<T>) -> Self {
+                        match value {
+                            Figure::Circle { radius } => Shape::Circle { radius },
+                            Figure::Square(field_0) => Shape::Square(field_0),
+                            Figure::Empty => Shape::Empty
+                        }
+                    }
+                }
+            },
+            parse_quote! {
+                impl<T> ::core::convert::From<Shape<T> > for Figure<T> {
+                    fn from(value: Shape<T>) -> Self {
+                        match value {
+                            Shape::Circle { radius } => Figure::Circle { radius },
+                            Shape::Square(field_0) => Figure::Square(field_0),
+                            Shape::Empty => Figure::Empty
+                        }
+                    }
+                }
+            },
+        ];
+        assert_eq!(
+            actual.iter().map(|i| i.to_token_stream().to_string()).collect::<Vec<String>>(),
+            expected.iter().map(|i| i.to_token_stream().to_string()).collect::<Vec<String>>());
+    }
+
+    #[test]
+    fn test_set_generic_alias() {
+        let mut state = State::new();
+        let input: Ident = parse_quote! { Page };
+        let arguments = parse_quote! { <User> };
+
+        let structure: Definition = parse_quote! {
+            struct Page<T> {
+                items: Vec<T>
+            }
+        };
+
+        let expected: ItemStruct = parse_quote! {
+            struct Page {
+                items: Vec<User>
+            }
+        };
+
+        state.variables.insert(input.to_string(), structure);
+        let actual = process_generic_alias(&mut state, &input, &arguments).unwrap();
+
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
 }
\ No newline at end of file
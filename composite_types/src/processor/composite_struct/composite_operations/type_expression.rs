@@ -0,0 +1,102 @@
+use crate::parser::composite_struct::type_expression::TypeExpression;
+use crate::processor::errors::{generate_not_found_error, generate_type_alias_operand_error};
+use crate::processor::State;
+use crate::processor::composite_struct::composite_operations::utility_operations::process_utility_operator;
+use crate::processor::composite_struct::composite_operations::utility_operations::generics::instantiate_generics;
+use crate::processor::composite_struct::composite_operations::type_alias::process_path_alias;
+use syn::Result;
+use crate::parser::definition::Definition;
+
+/// Evaluates a type expression into the structure it describes. Named types are looked up in the
+/// state relative to the module being processed and generic types are instantiated with their
+/// arguments, while nested operations are evaluated recursively and are never added to the state.
+/// Type aliases can't be operands, since they have no fields to operate on.
+pub fn process_type_expression(state: &mut State, expression: &TypeExpression) -> Result<Definition> {
+    let definition = match expression {
+        TypeExpression::Ident(ident) => match state.lookup(&state.key(ident)) {
+            None => generate_not_found_error(ident),
+            Some(structure) => Ok(structure.clone())
+        },
+        TypeExpression::Generic(ident, arguments) => match state.lookup(&state.key(ident)) {
+            None => generate_not_found_error(ident),
+            Some(structure) => instantiate_generics(structure, arguments)
+        },
+        TypeExpression::Path(path) => process_path_alias(state, path),
+        TypeExpression::UtilityOp(uo) => process_utility_operator(state, uo)
+    }?;
+    if let Definition::Type(_) = definition {
+        return Err(generate_type_alias_operand_error(expression, &definition))
+    }
+    Ok(definition)
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use quote::ToTokens;
+    use super::*;
+    use syn::{ItemStruct, parse_quote};
+
+    #[test]
+    fn test_process_type_expression_nested() {
+        let mut state = State::new();
+        let input = parse_quote! { Optional(Pick(Test, field1)) };
+
+        let structure = parse_quote! {
+            struct Test {
+                field1: FieldType,
+                field2: FieldType
+            }
+        };
+        state.variables.insert("Test".to_string(), structure);
+
+        let expected: ItemStruct = parse_quote! {
+            struct TestPickOptional {
+                field1: Option<FieldType>,
+            }
+        };
+
+        let actual = process_type_expression(&mut state, &input).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string()
+        );
+        assert_eq!(state.variables.len(), 1);
+    }
+
+    #[test]
+    fn test_process_type_expression_generic() {
+        let mut state = State::new();
+        let input = parse_quote! { Optional(Page<User>) };
+
+        let structure = parse_quote! {
+            struct Page<T> {
+                items: Vec<T>,
+                current: T
+            }
+        };
+        state.variables.insert("Page".to_string(), structure);
+
+        let expected: ItemStruct = parse_quote! {
+            struct PageOptional {
+                items: Option<Vec<User> >,
+                current: Option<User>
+            }
+        };
+
+        let actual = process_type_expression(&mut state, &input).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string()
+        );
+    }
+
+    #[test]
+    fn test_process_type_expression_not_found() {
+        let mut state = State::new();
+        let input = parse_quote! { Optional(Pick(Test, field1)) };
+
+        let actual = process_type_expression(&mut state, &input);
+        assert!(actual.is_err());
+    }
+}
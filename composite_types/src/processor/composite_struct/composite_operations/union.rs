@@ -0,0 +1,213 @@
+use proc_macro2::Ident;
+use syn::{Generics, ItemEnum, ItemImpl, parse_quote, PathArguments, Result, Type, Variant};
+use syn::punctuated::Punctuated;
+use syn::token::Comma;
+use crate::parser::composite_struct::type_expression::TypeExpression;
+use crate::parser::definition::Definition;
+use crate::processor::composite_struct::composite_operations::utility_operations::generics::{instantiate_generics, merge_generics};
+use crate::processor::errors::{combine_errors, generate_not_found_error};
+use crate::processor::State;
+
+/// Builds an enum with one tuple variant per member, named after the member's type, e.g.
+/// `enum Shape = Circle | Square;` becomes `enum Shape { Circle(Circle), Square(Square) }`.
+/// `From` conversions into the enum and `TryFrom` conversions back to each member are added to
+/// the state. Generic members given without arguments contribute their generic parameters to the
+/// enum, so `enum Paged = Page | Cursor;` is generic over the parameters of both.
+pub fn process_union(state: &mut State, name: &Ident, members: &[TypeExpression]) -> Result<Definition> {
+    let mut generics = Generics::default();
+    let mut variants: Punctuated<Variant, Comma> = Punctuated::new();
+    let mut errors = Vec::new();
+    for member in members {
+        let (ident, payload): (&Ident, Type) = match member {
+            TypeExpression::Ident(ident) => match state.lookup(&state.key(ident)) {
+                None => return generate_not_found_error(ident),
+                Some(definition) => {
+                    merge_generics(&mut generics, definition.generics());
+                    let (_, type_generics, _) = definition.generics().split_for_impl();
+                    (ident, parse_quote!(#ident #type_generics))
+                }
+            },
+            TypeExpression::Generic(ident, arguments) => match state.lookup(&state.key(ident)) {
+                None => return generate_not_found_error(ident),
+                Some(definition) => {
+                    instantiate_generics(definition, arguments)?;
+                    (ident, parse_quote!(#ident #arguments))
+                }
+            },
+            // The variant is named after the last segment, e.g. `User` for `models::User`
+            TypeExpression::Path(path) => {
+                let definition = state.lookup(&state.resolve(path)?).unwrap();
+                let last = path.segments.last().unwrap();
+                match &last.arguments {
+                    PathArguments::AngleBracketed(arguments) => {
+                        instantiate_generics(definition, arguments)?;
+                        (&last.ident, parse_quote!(#path))
+                    },
+                    _ => {
+                        merge_generics(&mut generics, definition.generics());
+                        let (_, type_generics, _) = definition.generics().split_for_impl();
+                        (&last.ident, parse_quote!(#path #type_generics))
+                    }
+                }
+            },
+            TypeExpression::UtilityOp(_) => {
+                errors.push(syn::Error::new(member.span(), format!(
+                    "`{0}` can not be a member of a union since it is not emitted as a type. Declare it first, e.g. `struct Name = {0};`",
+                    member)));
+                continue
+            }
+        };
+        if variants.iter().any(|variant| &variant.ident == ident) {
+            errors.push(syn::Error::new(ident.span(), format!("`{}` appears more than once in the union", ident)));
+            continue
+        }
+        variants.push(parse_quote!(#ident(#payload)));
+    }
+    combine_errors(errors)?;
+
+    let mut enumeration: ItemEnum = parse_quote! {
+        enum #name {
+            #variants
+        }
+    };
+    enumeration.generics = generics;
+    state.impls.insert(state.key(name), generate_union_conversions(&enumeration));
+    Ok(Definition::Enum(enumeration))
+}
+
+/// `From` and `TryFrom` conversions between a union and the type wrapped by each of its variants.
+/// A failed `TryFrom` conversion returns the union unchanged as its error.
+fn generate_union_conversions(enumeration: &ItemEnum) -> Vec<ItemImpl> {
+    let name = &enumeration.ident;
+    let (impl_generics, type_generics, where_clause) = enumeration.generics.split_for_impl();
+    enumeration.variants.iter().flat_map(|variant| {
+        let ident = &variant.ident;
+        let payload = &variant.fields.iter().next().unwrap().ty;
+        let from: ItemImpl = parse_quote! {
+            impl #impl_generics ::core::convert::From<#payload> for #name #type_generics #where_clause {
+                fn from(value: #payload) -> Self {
+                    #name::#ident(value)
+                }
+            }
+        };
+        let try_from: ItemImpl = parse_quote! {
+            impl #impl_generics ::core::convert::TryFrom<#name #type_generics> for #payload #where_clause {
+                type Error = #name #type_generics;
+
+                #[allow(unreachable_patterns)]
+                fn try_from(value: #name #type_generics) -> ::core::result::Result<Self, Self::Error> {
+                    match value {
+                        #name::#ident(value) => ::core::result::Result::Ok(value),
+                        other => ::core::result::Result::Err(other)
+                    }
+                }
+            }
+        };
+        vec![from, try_from]
+    }).collect()
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use quote::ToTokens;
+    use super::*;
+
+    fn state() -> State {
+        let mut state = State::new();
+        state.variables.insert("Circle".to_string(), parse_quote! {
+            struct Circle { radius: f64 }
+        });
+        state.variables.insert("Square".to_string(), parse_quote! {
+            struct Square(f64);
+        });
+        state.variables.insert("Page".to_string(), parse_quote! {
+            struct Page<T: Clone> { items: Vec<T> }
+        });
+        state
+    }
+
+    #[test]
+    fn test_process_union() {
+        let mut state = state();
+        let members: Vec<TypeExpression> = vec![parse_quote!(Circle), parse_quote!(Square)];
+
+        let expected: ItemEnum = parse_quote! {
+            enum Shape {
+                Circle(Circle),
+                Square(Square)
+            }
+        };
+
+        let actual = process_union(&mut state, &parse_quote!(Shape), &members).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+
+        let expected_from: ItemImpl = parse_quote! {
+            impl ::core::convert::From<Square> for Shape {
+                fn from(value: Square) -> Self {
+                    Shape::Square(value)
+                }
+            }
+        };
+        let impls = state.impls.get("Shape").unwrap();
+        assert_eq!(impls.len(), 4);
+        assert_eq!(
+            impls[2].to_token_stream().to_string(),
+            expected_from.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_union_generic() {
+        let mut state = state();
+        let members: Vec<TypeExpression> = vec![parse_quote!(Page), parse_quote!(Circle)];
+
+        let expected: ItemEnum = parse_quote! {
+            enum Paged<T: Clone> {
+                Page(Page<T>),
+                Circle(Circle)
+            }
+        };
+
+        let actual = process_union(&mut state, &parse_quote!(Paged), &members).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+
+        let expected_try_from: ItemImpl = parse_quote! {
+            impl<T: Clone> ::core::convert::TryFrom<Paged<T> > for Page<T> {
+                type Error = Paged<T>;
+
+                #[allow(unreachable_patterns)]
+                fn try_from(value: Paged<T>) -> ::core::result::Result<Self, Self::Error> {
+                    match value {
+                        Paged::Page(value) => ::core::result::Result::Ok(value),
+                        other => ::core::result::Result::Err(other)
+                    }
+                }
+            }
+        };
+        assert_eq!(
+            state.impls.get("Paged").unwrap()[1].to_token_stream().to_string(),
+            expected_try_from.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_union_duplicate_member() {
+        let mut state = state();
+        let members: Vec<TypeExpression> = vec![parse_quote!(Page<u8>), parse_quote!(Page<u16>)];
+
+        let actual = process_union(&mut state, &parse_quote!(Pages), &members);
+        assert_eq!(actual.err().unwrap().to_string(), "`Page` appears more than once in the union");
+    }
+
+    #[test]
+    fn test_process_union_nested_operation() {
+        let mut state = state();
+        let members: Vec<TypeExpression> = vec![parse_quote!(Circle), parse_quote!(Optional(Square))];
+
+        let actual = process_union(&mut state, &parse_quote!(Shape), &members);
+        assert!(actual.err().unwrap().to_string().starts_with("`Optional<Square>` can not be a member of a union"));
+    }
+}
@@ -0,0 +1,391 @@
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use proc_macro2::Ident;
+use quote::{format_ident, ToTokens};
+use syn::{GenericArgument, PathArguments, Result, Type};
+use crate::parser::composite_struct::type_expression::TypeExpression;
+use crate::parser::definition::Definition;
+use crate::processor::State;
+use crate::processor::state::parent;
+use crate::processor::composite_struct::composite_operations::type_expression::process_type_expression;
+use crate::processor::composite_struct::composite_operations::utility_operations::nullable::nullable_argument;
+use crate::processor::composite_struct::composite_operations::utility_operations::optional::process_optional;
+use crate::processor::composite_struct::composite_operations::utility_operations::required::process_required;
+
+/// Operations which are applied to a type and to every type it contains which is defined in the
+/// same macro.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeepOperation {
+    Optional,
+    Required,
+}
+
+impl DeepOperation {
+    /// Appended to the names of the types generated for the contained types, e.g. `AddressOptional`.
+    fn suffix(&self) -> &'static str {
+        match self {
+            DeepOperation::Optional => "Optional",
+            DeepOperation::Required => "Required"
+        }
+    }
+}
+
+/// Applies `operation` to every field of the type named by `expression`. Field types which name a
+/// struct or enum defined in the macro, directly or as the element of `Option`, a nullable wrapper,
+/// `Vec`, `VecDeque`, `Box`, `Rc` or `Arc` or as the value of `HashMap` or `BTreeMap`, are replaced
+/// with a generated type, e.g. `Address` with `AddressOptional`, which the operation is applied to
+/// in turn. Each generated type is added to the state once, so types which contain themselves
+/// refer back to the type being generated instead of recursing forever. References to the type
+/// the operation is applied to use `name`, the name its result is declared with, if there is one.
+/// Field types are resolved from the module of the type they belong to, e.g. `Address` in
+/// `models::User` refers to `models::Address`.
+pub fn process_deep(state: &mut State, expression: &TypeExpression, operation: DeepOperation,
+                    name: Option<&Ident>) -> Result<Definition> {
+    let definition = process_type_expression(state, expression)?;
+    let root = match expression {
+        TypeExpression::Ident(ident) => Some(state.key(ident)),
+        TypeExpression::Path(path) => Some(state.resolve(path)?),
+        _ => None
+    };
+    let mut deep = Deep {
+        state,
+        operation,
+        root: root.clone(),
+        name: name.cloned(),
+        visiting: root.clone().into_iter().collect(),
+        generated: BTreeSet::new(),
+    };
+    let module = root.as_deref().map_or_else(|| deep.state.module(), |root| parent(root).to_string());
+    let mut new_definition = deep.transform(&definition, &module)?;
+    // Without a name, the root is referenced through a generated type like any other
+    if root.is_some_and(|root| deep.generated.contains(&root)) {
+        let mut generated = new_definition.clone();
+        generated.set_ident(format_ident!("{}{}", definition.ident(), operation.suffix(), span = definition.ident().span()));
+        deep.emit(generated, definition.ident())?;
+    }
+    new_definition.set_ident(format_ident!("{}{}", definition.ident(), operation.to_string(), span = definition.ident().span()));
+    Ok(new_definition)
+}
+
+struct Deep<'a> {
+    state: &'a mut State,
+    operation: DeepOperation,
+    /// Key of the type the operation is applied to, if it is defined in the macro
+    root: Option<String>,
+    /// Name the result is declared with
+    name: Option<Ident>,
+    /// Types which are being transformed, from the outermost
+    visiting: Vec<String>,
+    /// Types which were referenced through their generated type
+    generated: BTreeSet<String>,
+}
+
+impl Deep<'_> {
+    /// Applies the operation to `definition`, which is declared in `module`.
+    fn transform(&mut self, definition: &Definition, module: &str) -> Result<Definition> {
+        let mut new_definition = match self.operation {
+            DeepOperation::Optional => process_optional(definition, &[], &self.state.settings.nullable)?,
+            DeepOperation::Required => process_required(definition, &[], &self.state.settings.nullable)?
+        };
+        let params = definition.generics().type_params()
+            .map(|param| param.ident.to_string())
+            .collect::<BTreeSet<String>>();
+        for field in new_definition.fields_mut() {
+            field.ty = self.rewrite(&field.ty, &params, module)?;
+        }
+        Ok(new_definition)
+    }
+
+    fn rewrite(&mut self, ty: &Type, params: &BTreeSet<String>, module: &str) -> Result<Type> {
+        let Type::Path(type_path) = ty else { return Ok(ty.clone()) };
+        if type_path.qself.is_some() {
+            return Ok(ty.clone())
+        }
+        let is_nullable = nullable_argument(ty, &self.state.settings.nullable).is_some();
+        let mut type_path = type_path.clone();
+        let is_local = type_path.path.leading_colon.is_none() && type_path.path.segments.len() == 1;
+        let Some(segment) = type_path.path.segments.last_mut() else { return Ok(ty.clone()) };
+        if is_local && !params.contains(&segment.ident.to_string()) && self.is_defined(&key(module, &segment.ident)) {
+            segment.ident = self.generate(&segment.ident, module)?;
+            return Ok(Type::Path(type_path))
+        }
+        let PathArguments::AngleBracketed(arguments) = &mut segment.arguments else { return Ok(ty.clone()) };
+        let mut types = arguments.args.iter_mut().filter_map(|argument| match argument {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None
+        });
+        let contained = match segment.ident.to_string().as_str() {
+            _ if is_nullable => types.next(),
+            "Vec" | "VecDeque" | "Box" | "Rc" | "Arc" => types.next(),
+            "HashMap" | "BTreeMap" => types.nth(1),
+            _ => None
+        };
+        if let Some(contained) = contained {
+            *contained = self.rewrite(contained, params, module)?;
+        }
+        Ok(Type::Path(type_path))
+    }
+
+    fn is_defined(&self, key: &str) -> bool {
+        matches!(self.state.lookup(key), Some(Definition::Struct(_) | Definition::Enum(_)))
+    }
+
+    /// Name of the type generated for the type named `ident` in `module`, which is generated first
+    /// if it doesn't exist yet.
+    fn generate(&mut self, ident: &Ident, module: &str) -> Result<Ident> {
+        let key = key(module, ident);
+        if self.root.as_ref() == Some(&key) {
+            if let Some(name) = &self.name {
+                return Ok(name.clone())
+            }
+        }
+        let generated = format_ident!("{}{}", ident, self.operation.suffix(), span = ident.span());
+        if self.visiting.contains(&key) || self.generated.contains(&key) {
+            self.generated.insert(key);
+            return Ok(generated)
+        }
+        let definition = self.state.lookup(&key).unwrap().clone();
+        self.visiting.push(key.clone());
+        let mut new_definition = self.transform(&definition, module)?;
+        self.visiting.pop();
+        new_definition.set_ident(generated.clone());
+        self.emit(new_definition, ident)?;
+        self.generated.insert(key);
+        Ok(generated)
+    }
+
+    /// Adds a generated type to the state. Generating the same type again, e.g. for another
+    /// operation, is allowed, but replacing a different type of the same name isn't.
+    fn emit(&mut self, definition: Definition, source: &Ident) -> Result<()> {
+        let key = self.state.key(definition.ident());
+        match self.state.variables.get(&key) {
+            Some(existing) if existing.to_token_stream().to_string() != definition.to_token_stream().to_string() => {
+                Err(syn::Error::new(source.span(), format!(
+                    "{} generates `{}` for `{}`, but this identifier is already in use",
+                    self.operation, definition.ident(), source)))
+            },
+            Some(_) => Ok(()),
+            None => {
+                self.state.insert(key, definition);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Key of the item named `name` in `module`, e.g. `models::Address`.
+fn key(module: &str, name: &Ident) -> String {
+    match module {
+        "" => name.to_string(),
+        module => format!("{}::{}", module, name)
+    }
+}
+
+// unit tests
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod tests {
+    use super::*;
+    use syn::{ItemStruct, parse_quote};
+
+    fn state(definitions: Vec<Definition>) -> State {
+        let mut state = State::new();
+        for definition in definitions {
+            state.variables.insert(definition.ident().to_string(), definition);
+        }
+        state
+    }
+
+    #[test]
+    fn test_process_deep_optional() {
+        let mut state = state(vec![
+            parse_quote! {
+                pub struct Address {
+                    city: String
+                }
+            },
+            parse_quote! {
+                pub struct User {
+                    name: String,
+                    address: Address,
+                    previous: Vec<Address>,
+                    contacts: HashMap<Address, Box<Address>>
+                }
+            },
+        ]);
+
+        let expected: ItemStruct = parse_quote! {
+            pub struct UserDeepOptional {
+                name: Option<String>,
+                address: Option<AddressOptional>,
+                previous: Option<Vec<AddressOptional> >,
+                contacts: Option<HashMap<Address, Box<AddressOptional> > >
+            }
+        };
+        let expected_address: ItemStruct = parse_quote! {
+            pub struct AddressOptional {
+                city: Option<String>
+            }
+        };
+
+        let actual = process_deep(&mut state, &parse_quote!(User), DeepOperation::Optional, None).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+        assert_eq!(
+            state.variables["AddressOptional"].to_token_stream().to_string(),
+            expected_address.to_token_stream().to_string());
+        assert_eq!(state.variables.len(), 3);
+    }
+
+    #[test]
+    fn test_process_deep_required() {
+        let mut state = state(vec![
+            parse_quote! {
+                struct Address {
+                    city: Option<String>
+                }
+            },
+            parse_quote! {
+                struct User {
+                    address: Option<Address>,
+                    previous: Option<Vec<Option<Address>>>
+                }
+            },
+        ]);
+
+        let expected: ItemStruct = parse_quote! {
+            struct UserDeepRequired {
+                address: AddressRequired,
+                previous: Vec<Option<AddressRequired> >
+            }
+        };
+
+        let actual = process_deep(&mut state, &parse_quote!(User), DeepOperation::Required,
+                                  Some(&parse_quote!(UserComplete))).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+        assert!(state.variables.contains_key("AddressRequired"));
+    }
+
+    #[test]
+    fn test_process_deep_optional_in_module() {
+        let mut state = state(vec![
+            parse_quote! {
+                struct Address {
+                    line: u8
+                }
+            },
+        ]);
+        state.modules.insert("models".to_string(), parse_quote!(mod models;));
+        state.variables.insert("models::Address".to_string(), parse_quote! {
+            pub struct Address {
+                city: String
+            }
+        });
+        state.variables.insert("models::User".to_string(), parse_quote! {
+            pub struct User {
+                address: Address
+            }
+        });
+
+        let actual = process_deep(&mut state, &parse_quote!(models::User), DeepOperation::Optional, None).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            "pub struct UserDeepOptional { address : Option < AddressOptional > }");
+        assert_eq!(
+            state.variables["AddressOptional"].to_token_stream().to_string(),
+            "pub struct AddressOptional { city : Option < String > }");
+    }
+
+    #[test]
+    fn test_process_deep_cycle() {
+        let mut state = state(vec![
+            parse_quote! {
+                struct Node<T> {
+                    value: T,
+                    parent: Option<Box<Node<T>>>,
+                    children: Vec<Node<T>>
+                }
+            },
+        ]);
+
+        let expected: ItemStruct = parse_quote! {
+            struct NodeDeepOptional<T> {
+                value: Option<T>,
+                parent: Option<Box<NodePatch<T> > >,
+                children: Option<Vec<NodePatch<T> > >
+            }
+        };
+
+        let actual = process_deep(&mut state, &parse_quote!(Node), DeepOperation::Optional,
+                                  Some(&parse_quote!(NodePatch))).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+        assert_eq!(state.variables.len(), 1);
+    }
+
+    #[test]
+    fn test_process_deep_cycle_without_name() {
+        let mut state = state(vec![
+            parse_quote! {
+                struct Team {
+                    lead: Member
+                }
+            },
+            parse_quote! {
+                struct Member {
+                    team: Box<Team>
+                }
+            },
+        ]);
+
+        let actual = process_deep(&mut state, &parse_quote!(Team), DeepOperation::Optional, None).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            "struct TeamDeepOptional { lead : Option < MemberOptional > }");
+        assert_eq!(
+            state.variables["MemberOptional"].to_token_stream().to_string(),
+            "struct MemberOptional { team : Option < Box < TeamOptional > > }");
+        assert_eq!(
+            state.variables["TeamOptional"].to_token_stream().to_string(),
+            "struct TeamOptional { lead : Option < MemberOptional > }");
+    }
+
+    #[test]
+    fn test_process_deep_conflict() {
+        let mut state = state(vec![
+            parse_quote! {
+                struct Address {
+                    city: String
+                }
+            },
+            parse_quote! {
+                struct AddressOptional {
+                    city: String
+                }
+            },
+            parse_quote! {
+                struct User {
+                    address: Address
+                }
+            },
+        ]);
+
+        let actual = process_deep(&mut state, &parse_quote!(User), DeepOperation::Optional, None);
+        assert_eq!(
+            actual.err().unwrap().to_string(),
+            "DeepOptional generates `AddressOptional` for `Address`, but this identifier is already in use");
+    }
+}
+
+impl Display for DeepOperation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeepOperation::Optional => write!(f, "DeepOptional"),
+            DeepOperation::Required => write!(f, "DeepRequired")
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use proc_macro2::Ident;
+use syn::{Fields, GenericParam, ItemType, parse_quote, Result};
+use crate::parser::definition::Definition;
+use crate::processor::composite_struct::composite_operations::utility_operations::generics::retain_used_generics;
+use crate::processor::composite_struct::composite_operations::utility_operations::helpers::validate_fields_exist;
+use crate::processor::errors::article;
+
+/// Type alias for the declared type of a named field of a struct, carrying over the field's
+/// documentation. Only the generic parameters used by the field's type are kept, and their bounds
+/// are dropped since bounds on type aliases are not enforced.
+pub fn process_field_type(definition: &Definition, field: &Ident) -> Result<Definition> {
+    let structure = match definition {
+        Definition::Struct(structure) => structure,
+        _ => return Err(syn::Error::new(field.span(), format!(
+            "FieldType can only be applied to structs, but `{}` is {} {}",
+            definition.ident(), article(definition.kind()), definition.kind())))
+    };
+    if let Fields::Unnamed(_) = structure.fields {
+        return Err(syn::Error::new(field.span(), format!(
+            "FieldType can only be applied to structs with named fields, but `{}` is a tuple struct", definition.ident())));
+    }
+    validate_fields_exist(definition, std::slice::from_ref(field))?;
+    let selected = structure.fields.iter().find(|f| f.ident.as_ref() == Some(field)).unwrap();
+
+    let ident = Ident::new(&format!("{}FieldType", structure.ident), structure.ident.span());
+    let docs = selected.attrs.iter().filter(|attr| attr.path().is_ident("doc"));
+    let ty = &selected.ty;
+    let alias: ItemType = parse_quote! {
+        #(#docs)*
+        type #ident = #ty;
+    };
+    let mut new_definition = Definition::Type(alias);
+    *new_definition.generics_mut() = structure.generics.clone();
+    retain_used_generics(&mut new_definition);
+
+    let generics = new_definition.generics_mut();
+    generics.where_clause = None;
+    for param in generics.params.iter_mut() {
+        match param {
+            GenericParam::Type(param) => {
+                param.colon_token = None;
+                param.bounds.clear();
+            },
+            GenericParam::Lifetime(param) => {
+                param.colon_token = None;
+                param.bounds.clear();
+            },
+            GenericParam::Const(_) => {}
+        }
+    }
+    Ok(new_definition)
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use quote::ToTokens;
+    use super::*;
+    use syn::{ItemEnum, ItemStruct};
+
+    #[test]
+    fn test_process_field_type() {
+        let input: ItemStruct = parse_quote! {
+            pub struct User {
+               /// Unique identifier
+               #[serde(rename = "ID")]
+               id: u64,
+               name: String
+            }
+        };
+
+        let expected: ItemType = parse_quote! {
+            /// Unique identifier
+            type UserFieldType = u64;
+        };
+
+        let actual = process_field_type(&input.into(), &parse_quote!(id)).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_field_type_generics() {
+        let input: ItemStruct = parse_quote! {
+            struct Page<'a, T: Clone, U> where T: Default {
+               items: Vec<&'a T>,
+               cursor: U
+            }
+        };
+
+        let expected: ItemType = parse_quote! {
+            type PageFieldType<'a, T> = Vec<&'a T>;
+        };
+
+        let actual = process_field_type(&input.into(), &parse_quote!(items)).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_field_type_unknown_field() {
+        let input: ItemStruct = parse_quote! {
+            struct User {
+               id: u64
+            }
+        };
+
+        let actual = process_field_type(&input.into(), &parse_quote!(email));
+        assert_eq!(actual.err().unwrap().to_string(), "Field `email` does not exist on type `User`");
+    }
+
+    #[test]
+    fn test_process_field_type_enum() {
+        let input: ItemEnum = parse_quote! {
+            enum Shape {
+               Circle { radius: f64 }
+            }
+        };
+
+        let actual = process_field_type(&input.into(), &parse_quote!(radius));
+        assert_eq!(
+            actual.err().unwrap().to_string(),
+            "FieldType can only be applied to structs, but `Shape` is an enum");
+    }
+}
@@ -0,0 +1,497 @@
+use std::collections::{BTreeMap, BTreeSet};
+use proc_macro2::Span;
+use quote::ToTokens;
+use syn::{AngleBracketedGenericArguments, Expr, GenericArgument, GenericParam, Generics, Lifetime,
+          parse_quote, Path, Result, Type, WhereClause, WherePredicate};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::token::Comma;
+use syn::visit::Visit;
+use syn::visit_mut::VisitMut;
+use crate::parser::definition::Definition;
+use crate::processor::errors::combine_errors;
+
+/// Names referenced by a piece of syntax. Type and const parameters are referenced through paths,
+/// e.g. `T`, `T::Item` or `N` in `[u8; N]`, while lifetimes are collected separately.
+#[derive(Default)]
+struct References {
+    idents: BTreeSet<String>,
+    lifetimes: BTreeSet<String>,
+}
+
+impl<'ast> Visit<'ast> for References {
+    fn visit_path(&mut self, path: &'ast Path) {
+        if path.leading_colon.is_none() {
+            if let Some(segment) = path.segments.first() {
+                self.idents.insert(segment.ident.to_string());
+            }
+        }
+        syn::visit::visit_path(self, path);
+    }
+
+    fn visit_lifetime(&mut self, lifetime: &'ast Lifetime) {
+        self.lifetimes.insert(lifetime.ident.to_string());
+    }
+}
+
+impl References {
+    fn contains(&self, param: &GenericParam) -> bool {
+        match param {
+            GenericParam::Type(param) => self.idents.contains(&param.ident.to_string()),
+            GenericParam::Const(param) => self.idents.contains(&param.ident.to_string()),
+            GenericParam::Lifetime(param) => self.lifetimes.contains(&param.lifetime.ident.to_string()),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.idents.len() + self.lifetimes.len()
+    }
+}
+
+/// A where predicate is kept as long as every generic parameter it constrains is kept.
+fn constrains_only(predicate: &WherePredicate, params: &Punctuated<GenericParam, Comma>, used: &References) -> bool {
+    let mut constrained = References::default();
+    match predicate {
+        WherePredicate::Type(predicate) => constrained.visit_type(&predicate.bounded_ty),
+        WherePredicate::Lifetime(predicate) => constrained.visit_lifetime(&predicate.lifetime),
+        _ => return true
+    }
+    params.iter().all(|param| !constrained.contains(param) || used.contains(param))
+}
+
+/// Removes the generic parameters of `definition` which are no longer referenced by its fields,
+/// since Rust rejects unused type and lifetime parameters. Parameters referenced by the bounds of
+/// a kept parameter are kept as well, and where predicates are dropped along with the parameters
+/// they constrain.
+pub fn retain_used_generics(definition: &mut Definition) {
+    let mut used = References::default();
+    definition.fields().for_each(|field| used.visit_type(&field.ty));
+    if let Definition::Type(alias) = definition {
+        used.visit_type(&alias.ty);
+    }
+    let generics = definition.generics_mut();
+
+    // Bounds may reference further parameters, so grow the set until it no longer changes
+    loop {
+        let previous = used.len();
+        for param in generics.params.iter() {
+            if used.contains(param) {
+                used.visit_generic_param(param);
+            }
+        }
+        if let Some(where_clause) = &generics.where_clause {
+            for predicate in where_clause.predicates.iter() {
+                if constrains_only(predicate, &generics.params, &used) {
+                    used.visit_where_predicate(predicate);
+                }
+            }
+        }
+        if used.len() == previous {
+            break
+        }
+    }
+
+    if let Some(where_clause) = &mut generics.where_clause {
+        where_clause.predicates = where_clause.predicates.clone().into_iter()
+            .filter(|predicate| constrains_only(predicate, &generics.params, &used))
+            .collect();
+        if where_clause.predicates.is_empty() {
+            generics.where_clause = None;
+        }
+    }
+    generics.params = generics.params.clone().into_iter().filter(|param| used.contains(param)).collect();
+    if generics.params.is_empty() {
+        generics.lt_token = None;
+        generics.gt_token = None;
+    }
+}
+
+/// Declares the lifetimes referenced by the fields of `definition` which are not declared yet, e.g.
+/// `'a` after a field type is replaced by `Cow<'a, str>`. They are added after the lifetimes which
+/// are already declared, in order of their names.
+pub fn declare_lifetimes(definition: &mut Definition) {
+    let mut used = References::default();
+    definition.fields().for_each(|field| used.visit_type(&field.ty));
+    if let Definition::Type(alias) = definition {
+        used.visit_type(&alias.ty);
+    }
+    let generics = definition.generics_mut();
+    let declared = generics.lifetimes().map(|param| param.lifetime.ident.to_string()).collect::<BTreeSet<String>>();
+    let missing = used.lifetimes.into_iter()
+        .filter(|lifetime| lifetime != "static" && lifetime != "_" && !declared.contains(lifetime))
+        .collect::<Vec<String>>();
+    for (index, lifetime) in missing.iter().enumerate() {
+        let lifetime = Lifetime::new(&format!("'{}", lifetime), Span::call_site());
+        generics.params.insert(declared.len() + index, parse_quote!(#lifetime));
+    }
+}
+
+/// Adds the generic parameters and where predicates of `other` to `generics`. A parameter which
+/// is declared by both keeps a single declaration carrying the bounds of both.
+pub fn merge_generics(generics: &mut Generics, other: &Generics) {
+    for param in other.params.iter() {
+        let existing = generics.params.iter_mut().find(|p| param_name(p) == param_name(param));
+        match (existing, param) {
+            (None, _) => generics.params.push(param.clone()),
+            (Some(GenericParam::Type(existing)), GenericParam::Type(param)) => {
+                for bound in param.bounds.iter() {
+                    if !existing.bounds.iter().any(|b| same_tokens(b, bound)) {
+                        existing.bounds.push(bound.clone());
+                    }
+                }
+            },
+            (Some(GenericParam::Lifetime(existing)), GenericParam::Lifetime(param)) => {
+                for bound in param.bounds.iter() {
+                    if !existing.bounds.iter().any(|b| b == bound) {
+                        existing.bounds.push(bound.clone());
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+    if let Some(other_where_clause) = &other.where_clause {
+        let where_clause = generics.where_clause.get_or_insert_with(|| WhereClause {
+            where_token: Default::default(),
+            predicates: Default::default(),
+        });
+        for predicate in other_where_clause.predicates.iter() {
+            if !where_clause.predicates.iter().any(|p| same_tokens(p, predicate)) {
+                where_clause.predicates.push(predicate.clone());
+            }
+        }
+    }
+    // Lifetimes must be declared before type and const parameters
+    let (lifetimes, others): (Vec<GenericParam>, Vec<GenericParam>) = generics.params.clone().into_iter()
+        .partition(|param| matches!(param, GenericParam::Lifetime(_)));
+    generics.params = lifetimes.into_iter().chain(others).collect();
+    if !generics.params.is_empty() {
+        generics.lt_token.get_or_insert_with(Default::default);
+        generics.gt_token.get_or_insert_with(Default::default);
+    }
+}
+
+/// Concrete arguments for generic parameters, keyed by parameter name.
+#[derive(Default)]
+struct Substitutions {
+    types: BTreeMap<String, Type>,
+    consts: BTreeMap<String, Expr>,
+    lifetimes: BTreeMap<String, Lifetime>,
+    errors: Vec<syn::Error>,
+}
+
+impl VisitMut for Substitutions {
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        if let Type::Path(type_path) = ty {
+            if type_path.qself.is_none() && type_path.path.leading_colon.is_none() {
+                let first = &type_path.path.segments[0].ident;
+                if let Some(argument) = self.types.get(&first.to_string()) {
+                    if type_path.path.segments.len() > 1 {
+                        self.errors.push(syn::Error::new(type_path.span(), format!(
+                            "Associated types of the generic parameter `{}` can not be instantiated", first)));
+                        return
+                    }
+                    *ty = argument.clone();
+                    return
+                }
+            }
+        }
+        syn::visit_mut::visit_type_mut(self, ty);
+    }
+
+    fn visit_generic_argument_mut(&mut self, argument: &mut GenericArgument) {
+        // `N` in `Buffer<N>` is parsed as a type, even when it names a const parameter
+        if let GenericArgument::Type(Type::Path(type_path)) = argument {
+            if let Some(expr) = type_path.path.get_ident().and_then(|ident| self.consts.get(&ident.to_string())) {
+                *argument = GenericArgument::Const(const_argument(expr));
+                return
+            }
+        }
+        syn::visit_mut::visit_generic_argument_mut(self, argument);
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if let Expr::Path(expr_path) = expr {
+            if let Some(argument) = expr_path.path.get_ident().and_then(|ident| self.consts.get(&ident.to_string())) {
+                *expr = argument.clone();
+                return
+            }
+        }
+        syn::visit_mut::visit_expr_mut(self, expr);
+    }
+
+    fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+        if let Some(argument) = self.lifetimes.get(&lifetime.ident.to_string()) {
+            *lifetime = argument.clone();
+        }
+    }
+}
+
+/// Const arguments which aren't literals must be wrapped in braces, e.g. `Buffer<{ 2 * 8 }>`.
+fn const_argument(expr: &Expr) -> Expr {
+    match expr {
+        Expr::Lit(_) | Expr::Block(_) => expr.clone(),
+        _ => parse_quote!({ #expr })
+    }
+}
+
+/// Instantiates the generic `definition` with `arguments`, e.g. `Page<User>`, substituting every
+/// parameter through the fields and where clause. Lifetime arguments may be left out, in which
+/// case the lifetime parameters are kept, and type and const parameters with defaults may be left
+/// out as well. The bounds of instantiated parameters are kept as where predicates on the
+/// concrete types, so that they are still checked by the compiler.
+pub fn instantiate_generics(definition: &Definition, arguments: &AngleBracketedGenericArguments) -> Result<Definition> {
+    let mut new_definition = definition.clone();
+    let mut substitutions = Substitutions::default();
+    let lifetime_arguments = arguments.args.iter()
+        .filter(|argument| matches!(argument, GenericArgument::Lifetime(_)))
+        .collect::<Vec<&GenericArgument>>();
+    let mut other_arguments = arguments.args.iter()
+        .filter(|argument| !matches!(argument, GenericArgument::Lifetime(_)));
+    let lifetime_params = definition.generics().lifetimes().count();
+    if !lifetime_arguments.is_empty() && lifetime_arguments.len() != lifetime_params {
+        return Err(syn::Error::new(arguments.span(), format!(
+            "`{}` expects {} lifetime arguments, but {} were given",
+            definition.ident(), lifetime_params, lifetime_arguments.len())));
+    }
+    let mut lifetime_arguments = lifetime_arguments.into_iter();
+    let mut predicates: Vec<WherePredicate> = Vec::new();
+
+    for param in definition.generics().params.iter() {
+        match param {
+            GenericParam::Lifetime(param) => {
+                if let Some(GenericArgument::Lifetime(argument)) = lifetime_arguments.next() {
+                    substitutions.lifetimes.insert(param.lifetime.ident.to_string(), argument.clone());
+                }
+            },
+            GenericParam::Type(param) => {
+                let argument = match (other_arguments.next(), &param.default) {
+                    (Some(GenericArgument::Type(argument)), _) => argument.clone(),
+                    (None, Some(default)) => default.clone(),
+                    (Some(argument), _) => return Err(syn::Error::new(argument.span(), format!(
+                        "Expected a type for the generic parameter `{}`", param.ident))),
+                    (None, None) => return Err(missing_argument_error(definition, arguments, &param.ident)),
+                };
+                if !param.bounds.is_empty() {
+                    let bounds = &param.bounds;
+                    predicates.push(parse_quote!(#argument: #bounds));
+                }
+                substitutions.types.insert(param.ident.to_string(), argument);
+            },
+            GenericParam::Const(param) => {
+                let argument = match (other_arguments.next(), &param.default) {
+                    (Some(GenericArgument::Const(argument)), _) => argument.clone(),
+                    (Some(GenericArgument::Type(Type::Path(argument))), _) if argument.path.get_ident().is_some() => {
+                        parse_quote!(#argument)
+                    },
+                    (None, Some(default)) => default.clone(),
+                    (Some(argument), _) => return Err(syn::Error::new(argument.span(), format!(
+                        "Expected a constant for the generic parameter `{}`", param.ident))),
+                    (None, None) => return Err(missing_argument_error(definition, arguments, &param.ident)),
+                };
+                substitutions.consts.insert(param.ident.to_string(), argument);
+            },
+        }
+    }
+    if let Some(argument) = other_arguments.next() {
+        return Err(syn::Error::new(argument.span(), format!(
+            "`{}` expects {} generic arguments, but more were given",
+            definition.ident(), definition.generics().type_params().count() + definition.generics().const_params().count())));
+    }
+
+    if let Some(where_clause) = &definition.generics().where_clause {
+        predicates.extend(where_clause.predicates.iter().cloned());
+    }
+    let generics = new_definition.generics_mut();
+    generics.params = generics.params.clone().into_iter()
+        .filter(|param| match param {
+            GenericParam::Lifetime(param) => !substitutions.lifetimes.contains_key(&param.lifetime.ident.to_string()),
+            _ => false
+        })
+        .collect();
+    generics.where_clause = None;
+    if !predicates.is_empty() {
+        generics.where_clause = Some(parse_quote!(where #(#predicates),*));
+    }
+    substitutions.visit_generics_mut(generics);
+    new_definition.fields_mut().for_each(|field| substitutions.visit_field_mut(field));
+    combine_errors(substitutions.errors)?;
+
+    // Predicates between instantiated lifetimes, e.g. `'static: 'static`, are no longer needed
+    let generics = new_definition.generics_mut();
+    let lifetimes = generics.lifetimes().map(|param| param.lifetime.clone()).collect::<Vec<Lifetime>>();
+    if let Some(where_clause) = &mut generics.where_clause {
+        where_clause.predicates = where_clause.predicates.clone().into_iter()
+            .filter(|predicate| match predicate {
+                WherePredicate::Lifetime(predicate) => lifetimes.contains(&predicate.lifetime),
+                _ => true
+            })
+            .collect();
+        if where_clause.predicates.is_empty() {
+            generics.where_clause = None;
+        }
+    }
+    if generics.params.is_empty() {
+        generics.lt_token = None;
+        generics.gt_token = None;
+    }
+    Ok(new_definition)
+}
+
+fn missing_argument_error(definition: &Definition, arguments: &AngleBracketedGenericArguments,
+                          param: &syn::Ident) -> syn::Error {
+    syn::Error::new(arguments.span(), format!(
+        "Missing an argument for the generic parameter `{}` of `{}`", param, definition.ident()))
+}
+
+fn param_name(param: &GenericParam) -> String {
+    match param {
+        GenericParam::Type(param) => param.ident.to_string(),
+        GenericParam::Const(param) => param.ident.to_string(),
+        GenericParam::Lifetime(param) => param.lifetime.to_string(),
+    }
+}
+
+fn same_tokens<T: ToTokens>(left: &T, right: &T) -> bool {
+    left.to_token_stream().to_string() == right.to_token_stream().to_string()
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::{ItemStruct, parse_quote};
+
+    #[test]
+    fn test_retain_used_generics() {
+        let input: ItemStruct = parse_quote! {
+            struct Page<'a, 'b, T: Clone, U, const N: usize> where T: Into<U>, 'b: 'a {
+               items: [&'a T; N]
+            }
+        };
+
+        let expected: ItemStruct = parse_quote! {
+            struct Page<'a, T: Clone, U, const N: usize> where T: Into<U> {
+               items: [&'a T; N]
+            }
+        };
+
+        let mut input = Definition::from(input);
+        retain_used_generics(&mut input);
+        assert_eq!(
+            input.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_retain_used_generics_removes_all() {
+        let input: ItemStruct = parse_quote! {
+            struct Page<'a, T> where T: Clone {
+               count: usize
+            }
+        };
+
+        let expected: ItemStruct = parse_quote! {
+            struct Page {
+               count: usize
+            }
+        };
+
+        let mut input = Definition::from(input);
+        retain_used_generics(&mut input);
+        assert_eq!(
+            input.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_merge_generics() {
+        let mut generics: Generics = parse_quote! { <T: Clone> };
+        let mut other: Generics = parse_quote! { <'a, T: Debug, U> };
+        other.where_clause = Some(parse_quote! { where U: Default });
+
+        let mut expected: Generics = parse_quote! { <'a, T: Clone + Debug, U> };
+        expected.where_clause = Some(parse_quote! { where U: Default });
+
+        merge_generics(&mut generics, &other);
+        assert_eq!(
+            generics.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+        assert_eq!(
+            generics.where_clause.to_token_stream().to_string(),
+            expected.where_clause.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_instantiate_generics() {
+        let input: ItemStruct = parse_quote! {
+            struct Page<'a, T: Clone, const N: usize> where T: Into<String> {
+               items: [&'a T; N],
+               next: Option<Buffer<N>>
+            }
+        };
+
+        let expected: ItemStruct = parse_quote! {
+            struct Page<'a> where User: Clone, User: Into<String> {
+               items: [&'a User; 10],
+               next: Option<Buffer<10>>
+            }
+        };
+
+        let actual = instantiate_generics(&input.into(), &parse_quote!(<User, 10>)).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_instantiate_generics_lifetimes_and_defaults() {
+        let input: ItemStruct = parse_quote! {
+            struct Page<'a, 'b, T, U = u64, const N: usize = 8> where 'b: 'a {
+               items: &'a [T],
+               cursor: &'b [U; N]
+            }
+        };
+
+        let expected: ItemStruct = parse_quote! {
+            struct Page {
+               items: &'static [User],
+               cursor: &'static [u64; 8]
+            }
+        };
+
+        let actual = instantiate_generics(&input.into(), &parse_quote!(<'static, 'static, User>)).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_instantiate_generics_missing_argument() {
+        let input: ItemStruct = parse_quote! {
+            struct Pair<T, U> {
+               first: T,
+               second: U
+            }
+        };
+
+        let actual = instantiate_generics(&input.into(), &parse_quote!(<User>));
+        assert_eq!(
+            actual.err().unwrap().to_string(),
+            "Missing an argument for the generic parameter `U` of `Pair`");
+    }
+
+    #[test]
+    fn test_instantiate_generics_too_many_arguments() {
+        let input: ItemStruct = parse_quote! {
+            struct Page<T> {
+               items: Vec<T>
+            }
+        };
+
+        let actual = instantiate_generics(&input.into(), &parse_quote!(<User, Account>));
+        assert_eq!(
+            actual.err().unwrap().to_string(),
+            "`Page` expects 1 generic arguments, but more were given");
+    }
+}
@@ -1,54 +1,48 @@
 use proc_macro2::Ident;
-use syn::{AngleBracketedGenericArguments, Field, GenericArgument, ItemStruct, PathArguments, PathSegment, Result, Type};
+use syn::{AngleBracketedGenericArguments, Field, GenericArgument, ItemStruct, Path, PathArguments, Result, Type};
+use crate::parser::definition::Definition;
+use crate::processor::errors::{combine_errors, generate_field_not_found_error, generate_variant_not_found_error};
 
 extern crate proc_macro2;
 
 pub fn generate_not_found_error(ident: &Ident) -> Result<()>{
-    return Err(syn::Error::new(ident.span(), format!("Required type {} does not exist", ident.to_string())))
+    Err(syn::Error::new(ident.span(), format!("Required type {} does not exist", ident)))
 }
 
 pub fn get_generics(field_type: &Type) -> Option<AngleBracketedGenericArguments> {
     if let syn::Type::Path(path) = field_type {
-        if let PathArguments::AngleBracketed(arguments) = path.path.segments.first()?.to_owned().arguments {
+        if let PathArguments::AngleBracketed(arguments) = path.path.segments.last()?.to_owned().arguments {
             return Some(arguments)
         }
     }
-    return None
+    None
 }
 
 fn is_generic_type_arg(arg: &GenericArgument) -> bool {
-    return if let syn::GenericArgument::Type(syn::Type::Path(type_path)) = arg {
-        true
-    } else {
-        false
-    }
+    matches!(arg, syn::GenericArgument::Type(_))
 }
 
 fn update_field_type(mut field: Field, top_level_type: Ident, generic_args: AngleBracketedGenericArguments) -> Field {
-    match &mut field.ty {
-        syn::Type::Path(type_path) => {
-            if let Some(segment) = type_path.path.segments.last_mut() {
-                segment.ident = top_level_type;
-                segment.arguments = PathArguments::AngleBracketed(generic_args);
-            }
+    if let syn::Type::Path(type_path) = &mut field.ty {
+        if let Some(segment) = type_path.path.segments.last_mut() {
+            segment.ident = top_level_type;
+            segment.arguments = PathArguments::AngleBracketed(generic_args);
         }
-        _ => {}
     }
     field
 }
 
 /// Wraps the current top level type as a generic argument, and sets the new field type
 /// as the passed in top level type. For example, if the field type is `FieldType<NestedFieldType, ...>`
-/// and the top level type is `NewFieldType`, the new field type will be `NewFieldType<FieldType<NestedFieldType, ...>>`
+/// and the top level type is `std::sync::Arc`, the new field type will be `std::sync::Arc<FieldType<NestedFieldType, ...>>`
 ///
 /// # Arguments
 ///
 /// * `field`: Field that is being operated on
-/// * `top_level_type`: New type which will process
+/// * `top_level_type`: Path of the new type, without generic arguments
 ///
 /// returns: Option<Field> If None, the operation failed to complete.
-
-pub fn wrap_field_as_generic_arg(field: Field, top_level_type: Ident) -> Option<Field> {
+pub fn wrap_field_as_generic_arg(field: Field, top_level_type: Path) -> Option<Field> {
     let mut field = field.clone();
     let new_generic_args = syn::punctuated::Punctuated::from_iter(vec![GenericArgument::Type(field.ty.clone())]);
     let new_generic_args = AngleBracketedGenericArguments {
@@ -57,16 +51,9 @@ pub fn wrap_field_as_generic_arg(field: Field, top_level_type: Ident) -> Option<
         args: new_generic_args,
         gt_token: syn::token::Gt::default()
     };
-    field.ty = Type::Path(syn::TypePath {
-        qself: None,
-        path: syn::Path {
-            leading_colon: None,
-            segments: syn::punctuated::Punctuated::from_iter(vec![PathSegment {
-                ident: top_level_type,
-                arguments: PathArguments::AngleBracketed(new_generic_args)
-            }])
-        }
-    });
+    let mut path = top_level_type;
+    path.segments.last_mut()?.arguments = PathArguments::AngleBracketed(new_generic_args);
+    field.ty = Type::Path(syn::TypePath { qself: None, path });
     Some(field)
 }
 
@@ -79,15 +66,14 @@ pub fn get_first_generic_type_arg(field: &Field) -> Option<Type> {
     let field_type = &field.ty;
     let arguments = get_generics(field_type)?;
     // Search for first valid generic argument
-    let search_result =  arguments.args.iter().enumerate().find(|(i, arg)| {
-        return is_generic_type_arg(arg)
+    let search_result =  arguments.args.iter().enumerate().find(|(_, arg)| {
+        is_generic_type_arg(arg)
     });
-    let (position, first_generic_type_arg) = search_result?;
-    let first_generic_type_arg = match first_generic_type_arg {
-        syn::GenericArgument::Type(syn::Type::Path(type_path)) => type_path.to_owned(),
-        _ => return None
-    };
-    Some(syn::Type::Path(first_generic_type_arg))
+    let (_, first_generic_type_arg) = search_result?;
+    match first_generic_type_arg {
+        syn::GenericArgument::Type(ty) => Some(ty.to_owned()),
+        _ => None
+    }
 }
 
 /// Promotes a generic argument to the top level of the type, overriding the existing
@@ -102,21 +88,34 @@ pub fn get_first_generic_type_arg(field: &Field) -> Option<Type> {
 pub fn promote_first_generic_argument(field: Field) -> Option<Field> {
     let mut field = field.clone();
     let field_type = &field.ty;
-    let mut arguments = get_generics(field_type)?;
+    let arguments = get_generics(field_type)?;
 
     // Search for first valid generic argument
-    let search_result =  arguments.args.iter().enumerate().find(|(i, arg)| {
-        return is_generic_type_arg(arg)
+    let search_result =  arguments.args.iter().enumerate().find(|(_, arg)| {
+        is_generic_type_arg(arg)
     });
-    let (position, first_generic_argument) = search_result?;
-    let first_generic_argument = match first_generic_argument {
-        syn::GenericArgument::Type(syn::Type::Path(type_path)) => type_path.to_owned(),
+    let (_, first_generic_argument) = search_result?;
+    field.ty = match first_generic_argument {
+        syn::GenericArgument::Type(ty) => ty.to_owned(),
         _ => return None
     };
-    field.ty = syn::Type::Path(first_generic_argument);
     Some(field)
 }
 
+/// Reports every listed field which is not a named field of a struct, or a variant of an enum.
+pub fn validate_fields_exist(definition: &Definition, fields: &[Ident]) -> Result<()> {
+    match definition {
+        Definition::Struct(structure) => combine_errors(fields.iter()
+            .filter(|field| !structure.fields.iter().any(|f| f.ident.as_ref() == Some(*field)))
+            .map(|field| generate_field_not_found_error(definition, field))),
+        Definition::Enum(enumeration) => combine_errors(fields.iter()
+            .filter(|variant| !enumeration.variants.iter().any(|v| &v.ident == *variant))
+            .map(|variant| generate_variant_not_found_error(definition, variant))),
+        Definition::Type(_) => combine_errors(fields.iter()
+            .map(|field| generate_field_not_found_error(definition, field)))
+    }
+}
+
 // write unit tests
 #[cfg(test)]
 mod tests {
@@ -177,7 +176,7 @@ mod tests {
         };
         let input_field = input.fields.iter().next().unwrap();
 
-        let new_field_type: Ident = parse_quote!(NewFieldType);
+        let new_field_type: Path = parse_quote!(NewFieldType);
         let expected: ItemStruct = parse_quote! {
             struct Test {
                field: NewFieldType<FieldType<'a, NestedFieldType, Goober::Nested>>
@@ -200,10 +199,10 @@ mod tests {
         };
         let input_field = input.fields.iter().next().unwrap();
 
-        let new_field_type: Ident = parse_quote!(NewFieldType);
+        let new_field_type: Path = parse_quote!(std::sync::Arc);
         let expected: ItemStruct = parse_quote! {
             struct Test {
-               field: NewFieldType<FieldType<NestedFieldType>>
+               field: std::sync::Arc<FieldType<NestedFieldType>>
             }
         };
         let expected_field = expected.fields.iter().next().unwrap();
@@ -0,0 +1,211 @@
+use proc_macro2::{Ident, Span};
+use syn::{Fields, ItemEnum, ItemImpl, LitStr, parse_quote, Result};
+use syn::ext::IdentExt;
+use crate::parser::definition::Definition;
+use crate::processor::errors::{article, combine_errors};
+
+/// Generates a fieldless enum with one variant per named field of a struct, in declaration order.
+/// Variants are the field names in UpperCamelCase, e.g. `created_at` becomes `CreatedAt`.
+pub fn process_key_of(definition: &Definition) -> Result<Definition> {
+    let keys = field_keys(definition)?;
+    let ident = Ident::new(&format!("{}KeyOf", definition.ident()), definition.ident().span());
+    let variants = keys.iter().map(|(_, variant)| variant);
+    let enumeration: ItemEnum = parse_quote! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        enum #ident {
+            #(#variants),*
+        }
+    };
+    Ok(Definition::Enum(enumeration))
+}
+
+/// `Display` and `FromStr` implementations which round-trip the original field names, and an
+/// `ALL` constant listing every variant of `keys`, which was produced from `definition` by
+/// [process_key_of].
+pub fn generate_key_impls(keys: &Definition, definition: &Definition) -> Result<Vec<ItemImpl>> {
+    let name = keys.ident();
+    let (fields, variants): (Vec<LitStr>, Vec<Ident>) = field_keys(definition)?.into_iter().unzip();
+    let count = variants.len();
+    let unknown_field = LitStr::new(&format!("`{{}}` is not a field of `{}`", definition.ident()), Span::call_site());
+    Ok(vec![
+        parse_quote! {
+            impl #name {
+                /// Every field, in declaration order.
+                pub const ALL: [#name; #count] = [#(#name::#variants),*];
+
+                /// Name of the field, as declared.
+                pub fn as_str(&self) -> &'static str {
+                    match *self {
+                        #(#name::#variants => #fields),*
+                    }
+                }
+            }
+        },
+        parse_quote! {
+            impl ::core::fmt::Display for #name {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    f.write_str(self.as_str())
+                }
+            }
+        },
+        parse_quote! {
+            impl ::core::str::FromStr for #name {
+                type Err = ::std::string::String;
+
+                fn from_str(s: &str) -> ::core::result::Result<Self, Self::Err> {
+                    match s {
+                        #(#fields => ::core::result::Result::Ok(#name::#variants),)*
+                        _ => ::core::result::Result::Err(::std::format!(#unknown_field, s))
+                    }
+                }
+            }
+        },
+    ])
+}
+
+/// Pairs the name of every field with the variant representing it.
+fn field_keys(definition: &Definition) -> Result<Vec<(LitStr, Ident)>> {
+    let structure = match definition {
+        Definition::Struct(structure) => structure,
+        _ => return Err(syn::Error::new(definition.ident().span(), format!(
+            "KeyOf can only be applied to structs, but `{}` is {} {}",
+            definition.ident(), article(definition.kind()), definition.kind())))
+    };
+    if let Fields::Unnamed(_) = structure.fields {
+        return Err(syn::Error::new(definition.ident().span(), format!(
+            "KeyOf can only be applied to structs with named fields, but `{}` is a tuple struct", definition.ident())));
+    }
+    let mut keys: Vec<(LitStr, Ident)> = Vec::new();
+    let mut errors = Vec::new();
+    for ident in structure.fields.iter().filter_map(|field| field.ident.as_ref()) {
+        let name = ident.unraw().to_string();
+        let variant = Ident::new(&to_upper_camel_case(&name), ident.span());
+        match keys.iter().find(|(_, existing)| existing == &variant) {
+            Some((existing, _)) => errors.push(syn::Error::new(ident.span(), format!(
+                "Fields `{}` and `{}` would both be represented by the variant `{}`", existing.value(), name, variant))),
+            None => keys.push((LitStr::new(&name, ident.span()), variant))
+        }
+    }
+    combine_errors(errors)?;
+    Ok(keys)
+}
+
+fn to_upper_camel_case(name: &str) -> String {
+    let camel_case: String = name.split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut characters = word.chars();
+            match characters.next() {
+                Some(first) => first.to_uppercase().chain(characters).collect(),
+                None => String::new()
+            }
+        })
+        .collect();
+    // Identifiers can't start with a digit, e.g. for a field named `_1`
+    match camel_case.chars().next() {
+        Some(first) if first.is_ascii_digit() => format!("_{}", camel_case),
+        _ => camel_case
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use quote::ToTokens;
+    use super::*;
+    use syn::{ItemEnum, ItemStruct};
+
+    #[test]
+    fn test_process_key_of() {
+        let input: ItemStruct = parse_quote! {
+            pub struct User {
+               id: u64,
+               created_at: u64,
+               r#type: String
+            }
+        };
+
+        let expected: ItemEnum = parse_quote! {
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+            enum UserKeyOf {
+               Id,
+               CreatedAt,
+               Type
+            }
+        };
+
+        let actual = process_key_of(&input.into()).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_key_of_tuple_struct() {
+        let input: ItemStruct = parse_quote! {
+            struct Meters(f64);
+        };
+
+        let actual = process_key_of(&input.into());
+        assert_eq!(
+            actual.err().unwrap().to_string(),
+            "KeyOf can only be applied to structs with named fields, but `Meters` is a tuple struct");
+    }
+
+    #[test]
+    fn test_process_key_of_conflicting_variants() {
+        let input: ItemStruct = parse_quote! {
+            struct User {
+               created_at: u64,
+               createdAt: u64
+            }
+        };
+
+        let actual = process_key_of(&input.into());
+        assert_eq!(
+            actual.err().unwrap().to_string(),
+            "Fields `created_at` and `createdAt` would both be represented by the variant `CreatedAt`");
+    }
+
+    #[test]
+    fn test_generate_key_impls() {
+        let input: ItemStruct = parse_quote! {
+            struct User {
+               id: u64,
+               created_at: u64
+            }
+        };
+        let definition: Definition = input.into();
+        let mut keys = process_key_of(&definition).unwrap();
+        keys.set_ident(parse_quote!(UserField));
+
+        let expected: ItemImpl = parse_quote! {
+            impl UserField {
+                /// Every field, in declaration order.
+                pub const ALL: [UserField; 2usize] = [UserField::Id, UserField::CreatedAt];
+
+                /// Name of the field, as declared.
+                pub fn as_str(&self) -> &'static str {
+                    match *self {
+                        UserField::Id => "id",
+                        UserField::CreatedAt => "created_at"
+                    }
+                }
+            }
+        };
+
+        let actual = generate_key_impls(&keys, &definition).unwrap();
+        assert_eq!(actual.len(), 3);
+        assert_eq!(
+            actual[0].to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_to_upper_camel_case() {
+        assert_eq!(to_upper_camel_case("created_at"), "CreatedAt");
+        assert_eq!(to_upper_camel_case("_private"), "Private");
+        assert_eq!(to_upper_camel_case("_1"), "_1");
+        assert_eq!(to_upper_camel_case("createdAt"), "CreatedAt");
+    }
+}
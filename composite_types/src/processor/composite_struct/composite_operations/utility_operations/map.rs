@@ -0,0 +1,245 @@
+use syn::{Result, Type};
+use syn::visit_mut::VisitMut;
+use crate::parser::composite_struct::map_rule::{metavariable, MapRule};
+use crate::parser::definition::Definition;
+use crate::processor::composite_struct::composite_operations::utility_operations::generics::{declare_lifetimes, retain_used_generics};
+use crate::processor::composite_struct::composite_operations::utility_operations::patterns::{Bindings, match_type};
+
+/// Rewrites the types of every field of `definition` with `rules`. At each type, from the outside
+/// in, the first rule whose pattern matches replaces it, and the types bound to its metavariables
+/// are rewritten before they are substituted into the replacement. Types which no rule matches are
+/// kept, with the types they contain rewritten. Lifetimes introduced by replacements are declared,
+/// and generic parameters which are no longer used are removed.
+pub fn process_map(definition: &Definition, rules: &[MapRule]) -> Result<Definition> {
+    let mut new_definition = definition.clone();
+    new_definition.append_to_ident("Map");
+    let mut rewriter = Rewriter { rules };
+    new_definition.fields_mut().for_each(|field| rewriter.visit_type_mut(&mut field.ty));
+    if let Definition::Type(alias) = &mut new_definition {
+        rewriter.visit_type_mut(&mut alias.ty);
+    }
+    declare_lifetimes(&mut new_definition);
+    retain_used_generics(&mut new_definition);
+    Ok(new_definition)
+}
+
+struct Rewriter<'a> {
+    rules: &'a [MapRule],
+}
+
+impl Rewriter<'_> {
+    fn rewrite(&mut self, ty: &Type) -> Type {
+        for rule in self.rules {
+            let mut bindings = Bindings::new();
+            if !match_type(&rule.pattern, ty, &mut bindings) {
+                continue
+            }
+            let rewritten = self.rewrite_children(ty);
+            // A pattern such as `$T` or `_` binds the type itself, which can't be rewritten again
+            let bindings = match rule.pattern {
+                Type::Infer(_) => bindings,
+                _ if metavariable(&rule.pattern).is_some() => bindings.into_iter()
+                    .map(|(name, _)| (name, rewritten.clone()))
+                    .collect(),
+                _ => bindings.into_iter()
+                    .map(|(name, bound)| (name, self.rewrite(&bound)))
+                    .collect()
+            };
+            let mut replacement = rule.replacement.clone();
+            Substitution { bindings: &bindings }.visit_type_mut(&mut replacement);
+            return replacement
+        }
+        self.rewrite_children(ty)
+    }
+
+    fn rewrite_children(&mut self, ty: &Type) -> Type {
+        let mut rewritten = ty.clone();
+        syn::visit_mut::visit_type_mut(self, &mut rewritten);
+        rewritten
+    }
+}
+
+impl VisitMut for Rewriter<'_> {
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        *ty = self.rewrite(ty);
+    }
+}
+
+/// Replaces the metavariables of a replacement with the types bound to them, which the pattern
+/// binds all of.
+struct Substitution<'a> {
+    bindings: &'a Bindings,
+}
+
+impl VisitMut for Substitution<'_> {
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        match metavariable(ty) {
+            Some(name) => if let Some((_, bound)) = self.bindings.iter().find(|(bound, _)| *bound == name) {
+                *ty = bound.clone();
+            },
+            None => syn::visit_mut::visit_type_mut(self, ty)
+        }
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use proc_macro2::TokenStream;
+    use quote::ToTokens;
+    use super::*;
+    use syn::{ItemEnum, ItemStruct, parse_quote};
+    use syn::parse::{Parse, Parser};
+    use crate::parser::composite_struct::map_rule::replace_metavariables;
+
+    fn rule(tokens: TokenStream) -> MapRule {
+        MapRule::parse.parse2(replace_metavariables(tokens)).unwrap()
+    }
+
+    #[test]
+    fn test_process_map() {
+        let input: ItemStruct = parse_quote! {
+            pub struct User {
+               pub id: u64,
+               nickname: Option<String>,
+               tags: Vec<Option<u8>>
+            }
+        };
+        let rules = vec![rule(parse_quote!(Option<$T> => Vec<$T>))];
+
+        let expected: ItemStruct = parse_quote! {
+            pub struct UserMap {
+               pub id: u64,
+               nickname: Vec<String>,
+               tags: Vec<Vec<u8> >
+            }
+        };
+
+        let actual = process_map(&input.into(), &rules).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_map_declares_lifetimes() {
+        let input: ItemStruct = parse_quote! {
+            struct User<T> {
+               name: String,
+               aliases: Vec<String>,
+               data: T
+            }
+        };
+        let rules = vec![rule(parse_quote!(String => Cow<'a, str>))];
+
+        let expected: ItemStruct = parse_quote! {
+            struct UserMap<'a, T> {
+               name: Cow<'a, str>,
+               aliases: Vec<Cow<'a, str> >,
+               data: T
+            }
+        };
+
+        let actual = process_map(&input.into(), &rules).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_map_rewrites_bindings() {
+        let input: ItemStruct = parse_quote! {
+            struct Settings {
+               theme: Option<Option<String>>,
+               limits: HashMap<String, u32>
+            }
+        };
+        let rules = vec![
+            rule(parse_quote!(Option<$T> => Patch<$T>)),
+            rule(parse_quote!(HashMap<$K, $V> => BTreeMap<$K, Option<$V>>)),
+            rule(parse_quote!(String => Box<str>)),
+        ];
+
+        let expected: ItemStruct = parse_quote! {
+            struct SettingsMap {
+               theme: Patch<Patch<Box<str> > >,
+               limits: BTreeMap<Box<str>, Option<u32> >
+            }
+        };
+
+        let actual = process_map(&input.into(), &rules).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_map_catch_all() {
+        let input: ItemEnum = parse_quote! {
+            enum Event {
+               Click { x: u32, target: Option<String> },
+               Key(char)
+            }
+        };
+        let rules = vec![
+            rule(parse_quote!(Option<$T> => $T)),
+            rule(parse_quote!($T => Signal<$T>)),
+        ];
+
+        let expected: ItemEnum = parse_quote! {
+            enum EventMap {
+               Click { x: Signal<u32>, target: Signal<String> },
+               Key(Signal<char>)
+            }
+        };
+
+        let actual = process_map(&input.into(), &rules).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_map_repeated_metavariable() {
+        let input: ItemStruct = parse_quote! {
+            struct Ranges {
+               bytes: (u8, u8),
+               mixed: (u8, u16)
+            }
+        };
+        let rules = vec![rule(parse_quote!(($T, $T) => Range<$T>))];
+
+        let expected: ItemStruct = parse_quote! {
+            struct RangesMap {
+               bytes: Range<u8>,
+               mixed: (u8, u16)
+            }
+        };
+
+        let actual = process_map(&input.into(), &rules).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_map_removes_unused_generics() {
+        let input: ItemStruct = parse_quote! {
+            struct Cache<K: Ord, V> {
+               entries: BTreeMap<K, V>
+            }
+        };
+        let rules = vec![rule(parse_quote!(BTreeMap<_, $V> => Vec<$V>))];
+
+        let expected: ItemStruct = parse_quote! {
+            struct CacheMap<V> {
+               entries: Vec<V>
+            }
+        };
+
+        let actual = process_map(&input.into(), &rules).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+}
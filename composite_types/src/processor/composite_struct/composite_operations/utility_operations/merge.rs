@@ -0,0 +1,347 @@
+use syn::{Field, Fields, FieldsNamed, FieldsUnnamed, ItemEnum, ItemStruct, Result, token, Variant};
+use crate::parser::composite_struct::type_expression::TypeExpression;
+use crate::parser::composite_struct::utility_operations::ConflictPolicy;
+use crate::processor::composite_struct::composite_operations::utility_operations::generics::{merge_generics, retain_used_generics};
+use crate::parser::definition::Definition;
+use crate::processor::errors::{article, combine_errors, generate_duplicate_field_error, generate_duplicate_variant_error,
+                               generate_type_alias_operand_error};
+
+/// Combines the fields of every struct into one flat struct, or the variants of every enum into
+/// one enum. The first definition provides the attributes and visibility of the result. Fields or
+/// variants which share a name are resolved with `conflict`: [ConflictPolicy::First] keeps the
+/// earliest and [ConflictPolicy::Last] the latest, at the position of the earliest. The others are
+/// dropped, whatever their types.
+///
+/// Tuple structs are merged by concatenating their fields, and unit structs contribute no fields.
+/// Structs with named fields cannot be merged with tuple structs, and structs cannot be merged
+/// with enums. The generic parameters of every definition are combined by name, and those unused
+/// by the kept fields are removed.
+///
+/// # Arguments
+///
+/// * `definitions`: The definitions to merge, paired with the expression which produced them.
+/// * `conflict`: Policy applied to fields or variants with the same name.
+pub fn process_merge(definitions: &[(&TypeExpression, Definition)], conflict: ConflictPolicy) -> Result<Definition> {
+    let (first_expression, first) = &definitions[0];
+    if let Some((expression, definition)) = definitions.iter().find(|(_, d)| d.kind() != first.kind()) {
+        return Err(syn::Error::new(expression.span(),
+                                   format!("`{}` is {} {} and cannot be merged with `{}`, which is {} {}",
+                                           expression, article(definition.kind()), definition.kind(),
+                                           first_expression, article(first.kind()), first.kind())));
+    }
+
+    let mut new_definition = match first {
+        Definition::Struct(_) => {
+            let structures = definitions.iter().filter_map(|(expression, definition)| match definition {
+                Definition::Struct(structure) => Some((*expression, structure)),
+                _ => None
+            }).collect::<Vec<(&TypeExpression, &ItemStruct)>>();
+            Definition::Struct(merge_structs(&structures, conflict)?)
+        },
+        Definition::Enum(_) => {
+            let enumerations = definitions.iter().filter_map(|(expression, definition)| match definition {
+                Definition::Enum(enumeration) => Some((*expression, enumeration)),
+                _ => None
+            }).collect::<Vec<(&TypeExpression, &ItemEnum)>>();
+            Definition::Enum(merge_enums(&enumerations, conflict)?)
+        },
+        Definition::Type(_) => return Err(generate_type_alias_operand_error(first_expression, first))
+    };
+    new_definition.append_to_ident("Merge");
+    for (_, definition) in definitions.iter().skip(1) {
+        merge_generics(new_definition.generics_mut(), definition.generics());
+    }
+    retain_used_generics(&mut new_definition);
+    Ok(new_definition)
+}
+
+fn merge_structs(structures: &[(&TypeExpression, &ItemStruct)], conflict: ConflictPolicy) -> Result<ItemStruct> {
+    let (_, first) = structures[0];
+    let mut new_struct = first.clone();
+
+    let named = structures.iter().find(|(_, s)| matches!(s.fields, Fields::Named(_)));
+    let unnamed = structures.iter().find(|(_, s)| matches!(s.fields, Fields::Unnamed(_)));
+    if let (Some((named, _)), Some((unnamed, _))) = (named, unnamed) {
+        return Err(syn::Error::new(unnamed.span(),
+                                   format!("`{}` has unnamed fields and cannot be merged with `{}`, which has named fields",
+                                           unnamed, named)));
+    }
+
+    let mut fields: Vec<(&TypeExpression, Field)> = Vec::new();
+    let mut errors: Vec<syn::Error> = Vec::new();
+    for (expression, structure) in structures {
+        for field in structure.fields.iter() {
+            // Unnamed fields never conflict since they are identified by position
+            let existing = fields.iter().position(|(_, f)| f.ident.is_some() && f.ident == field.ident);
+            match (existing, conflict) {
+                (None, _) => fields.push((expression, field.clone())),
+                (Some(_), ConflictPolicy::First) => {},
+                (Some(position), ConflictPolicy::Last) => fields[position] = (expression, field.clone()),
+                (Some(position), ConflictPolicy::Error) => {
+                    let (source, _) = fields[position];
+                    errors.push(generate_duplicate_field_error(field.ident.as_ref().unwrap(), source, expression));
+                }
+            }
+        }
+    }
+
+    combine_errors(errors)?;
+
+    let fields = fields.into_iter().map(|(_, field)| field);
+    if named.is_some() {
+        new_struct.semi_token = None;
+        new_struct.fields = Fields::Named(FieldsNamed {
+            brace_token: token::Brace::default(),
+            named: fields.collect()
+        });
+    } else if unnamed.is_some() {
+        new_struct.semi_token = Some(token::Semi::default());
+        new_struct.fields = Fields::Unnamed(FieldsUnnamed {
+            paren_token: token::Paren::default(),
+            unnamed: fields.collect()
+        });
+    } else {
+        new_struct.semi_token = Some(token::Semi::default());
+        new_struct.fields = Fields::Unit;
+    }
+    Ok(new_struct)
+}
+
+fn merge_enums(enumerations: &[(&TypeExpression, &ItemEnum)], conflict: ConflictPolicy) -> Result<ItemEnum> {
+    let (_, first) = enumerations[0];
+    let mut new_enum = first.clone();
+
+    let mut variants: Vec<(&TypeExpression, Variant)> = Vec::new();
+    let mut errors: Vec<syn::Error> = Vec::new();
+    for (expression, enumeration) in enumerations {
+        for variant in enumeration.variants.iter() {
+            let existing = variants.iter().position(|(_, v)| v.ident == variant.ident);
+            match (existing, conflict) {
+                (None, _) => variants.push((expression, variant.clone())),
+                (Some(_), ConflictPolicy::First) => {},
+                (Some(position), ConflictPolicy::Last) => variants[position] = (expression, variant.clone()),
+                (Some(position), ConflictPolicy::Error) => {
+                    let (source, _) = variants[position];
+                    errors.push(generate_duplicate_variant_error(&variant.ident, source, expression));
+                }
+            }
+        }
+    }
+
+    combine_errors(errors)?;
+
+    new_enum.variants = variants.into_iter().map(|(_, variant)| variant).collect();
+    Ok(new_enum)
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use quote::ToTokens;
+    use super::*;
+    use syn::parse_quote;
+
+    fn merge(conflict: ConflictPolicy) -> Result<Definition> {
+        let first: ItemStruct = parse_quote! {
+            pub struct BankAccount {
+               pub balance: i32,
+               updated_at: u64
+            }
+        };
+        let second: ItemStruct = parse_quote! {
+            struct Audit {
+               created_at: u64,
+               updated_at: Option<u64>
+            }
+        };
+        let first_expression: TypeExpression = parse_quote!(BankAccount);
+        let second_expression: TypeExpression = parse_quote!(Audit);
+        process_merge(&[(&first_expression, first.into()), (&second_expression, second.into())], conflict)
+    }
+
+    #[test]
+    fn test_process_merge_first() {
+        let expected: ItemStruct = parse_quote! {
+            pub struct BankAccountMerge {
+               pub balance: i32,
+               updated_at: u64,
+               created_at: u64
+            }
+        };
+
+        let actual = merge(ConflictPolicy::First).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_merge_last() {
+        let expected: ItemStruct = parse_quote! {
+            pub struct BankAccountMerge {
+               pub balance: i32,
+               updated_at: Option<u64>,
+               created_at: u64
+            }
+        };
+
+        let actual = merge(ConflictPolicy::Last).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_merge_conflict_types() {
+        let first: ItemStruct = parse_quote!(struct User { id: u64, name: String });
+        let second: ItemStruct = parse_quote!(struct Legacy { id: String });
+        let third: ItemStruct = parse_quote!(struct Record { #[serde(rename = "uuid")] id: Uuid });
+        let expressions: Vec<TypeExpression> = vec![parse_quote!(User), parse_quote!(Legacy), parse_quote!(Record)];
+        let definitions = vec![
+            (&expressions[0], first.into()),
+            (&expressions[1], second.into()),
+            (&expressions[2], third.into()),
+        ];
+
+        let field_types = |definition: Definition| definition.fields()
+            .map(|field| format!("{}: {}", field.ident.as_ref().unwrap(), field.ty.to_token_stream()))
+            .collect::<Vec<String>>();
+        let actual = process_merge(&definitions, ConflictPolicy::First).unwrap();
+        assert_eq!(field_types(actual), vec!["id: u64", "name: String"]);
+        let actual = process_merge(&definitions, ConflictPolicy::Last).unwrap();
+        assert_eq!(actual.fields().next().unwrap().attrs.len(), 1);
+        assert_eq!(field_types(actual), vec!["id: Uuid", "name: String"]);
+    }
+
+    #[test]
+    fn test_process_merge_tuple_structs() {
+        let first: ItemStruct = parse_quote! {
+            struct Meters(pub f64);
+        };
+        let second: ItemStruct = parse_quote! {
+            struct Marker;
+        };
+        let third: ItemStruct = parse_quote! {
+            struct Seconds(u64, u32);
+        };
+        let expressions: Vec<TypeExpression> = vec![parse_quote!(Meters), parse_quote!(Marker), parse_quote!(Seconds)];
+
+        let expected: ItemStruct = parse_quote! {
+            struct MetersMerge(pub f64, u64, u32);
+        };
+
+        let actual = process_merge(&[
+            (&expressions[0], first.into()), (&expressions[1], second.into()), (&expressions[2], third.into())
+        ], ConflictPolicy::Error).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_merge_named_with_tuple_struct() {
+        let first: ItemStruct = parse_quote! {
+            struct BankAccount {
+               balance: i32
+            }
+        };
+        let second: ItemStruct = parse_quote! {
+            struct Meters(f64);
+        };
+        let expressions: Vec<TypeExpression> = vec![parse_quote!(BankAccount), parse_quote!(Meters)];
+
+        let actual = process_merge(&[(&expressions[0], first.into()), (&expressions[1], second.into())], ConflictPolicy::Error);
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn test_process_merge_conflict() {
+        let actual = merge(ConflictPolicy::Error).err().unwrap();
+        assert!(actual.to_string().starts_with("Field `updated_at` is defined by both `BankAccount` and `Audit`."));
+    }
+
+    #[test]
+    fn test_process_merge_generics() {
+        let first: ItemStruct = parse_quote! {
+            struct Page<T: Clone> {
+               items: Vec<T>
+            }
+        };
+        let second: ItemStruct = parse_quote! {
+            struct Cursor<'a, T: Debug> where T: Default {
+               next: Option<&'a T>
+            }
+        };
+        let first_expression: TypeExpression = parse_quote!(Page);
+        let second_expression: TypeExpression = parse_quote!(Cursor);
+
+        let expected: ItemStruct = parse_quote! {
+            struct PageMerge<'a, T: Clone + Debug> where T: Default {
+               items: Vec<T>,
+               next: Option<&'a T>
+            }
+        };
+
+        let actual = process_merge(&[(&first_expression, first.into()), (&second_expression, second.into())], ConflictPolicy::Error).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_merge_enums() {
+        let first: ItemEnum = parse_quote! {
+            pub enum Circle {
+               Circle { radius: f64 },
+               Empty
+            }
+        };
+        let second: ItemEnum = parse_quote! {
+            enum Square {
+               Square(f64),
+               Empty = 2
+            }
+        };
+        let expressions: Vec<TypeExpression> = vec![parse_quote!(Circle), parse_quote!(Square)];
+
+        let expected: ItemEnum = parse_quote! {
+            pub enum CircleMerge {
+               Circle { radius: f64 },
+               Empty = 2,
+               Square(f64)
+            }
+        };
+
+        let actual = process_merge(&[
+            (&expressions[0], first.clone().into()), (&expressions[1], second.clone().into())
+        ], ConflictPolicy::Last).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+
+        let actual = process_merge(&[
+            (&expressions[0], first.into()), (&expressions[1], second.into())
+        ], ConflictPolicy::Error).err().unwrap();
+        assert!(actual.to_string().starts_with("Variant `Empty` is defined by both `Circle` and `Square`."));
+    }
+
+    #[test]
+    fn test_process_merge_struct_with_enum() {
+        let first: ItemStruct = parse_quote! {
+            struct BankAccount {
+               balance: i32
+            }
+        };
+        let second: ItemEnum = parse_quote! {
+            enum Status {
+               Open
+            }
+        };
+        let expressions: Vec<TypeExpression> = vec![parse_quote!(BankAccount), parse_quote!(Status)];
+
+        let actual = process_merge(&[(&expressions[0], first.into()), (&expressions[1], second.into())], ConflictPolicy::Error);
+        assert_eq!(
+            actual.err().unwrap().to_string(),
+            "`Status` is an enum and cannot be merged with `BankAccount`, which is a struct");
+    }
+}
@@ -1,37 +1,86 @@
 use crate::parser::composite_struct::utility_operations::UtilityOperation;
 use crate::processor::State;
-use crate::processor::errors::generate_not_found_error;
-use syn::{ItemStruct, Result};
+use crate::processor::composite_struct::composite_operations::type_expression::process_type_expression;
+use syn::Result;
+use crate::parser::definition::Definition;
 
 use crate::processor::composite_struct::composite_operations::utility_operations::optional::process_optional;
 use crate::processor::composite_struct::composite_operations::utility_operations::required::process_required;
+use crate::processor::composite_struct::composite_operations::utility_operations::pick::process_pick;
+use crate::processor::composite_struct::composite_operations::utility_operations::omit::process_omit;
+use crate::processor::composite_struct::composite_operations::utility_operations::merge::process_merge;
+use crate::processor::composite_struct::composite_operations::utility_operations::key_of::process_key_of;
+use crate::processor::composite_struct::composite_operations::utility_operations::field_type::process_field_type;
+use crate::processor::composite_struct::composite_operations::utility_operations::rename::process_rename;
+use crate::processor::composite_struct::composite_operations::utility_operations::wrap::process_wrap;
+use crate::processor::composite_struct::composite_operations::utility_operations::unwrap::process_unwrap;
+use crate::processor::composite_struct::composite_operations::utility_operations::map::process_map;
+use crate::processor::composite_struct::composite_operations::utility_operations::deep::{DeepOperation, process_deep};
 
 mod required;
 mod optional;
+mod selectors;
+pub mod generics;
+mod pick;
+mod omit;
+mod merge;
+pub mod key_of;
+mod field_type;
+pub mod rename;
+mod wrap;
+mod unwrap;
+mod patterns;
+mod map;
+pub mod deep;
+mod nullable;
+#[allow(dead_code)]
 mod helpers;
 
 pub fn process_utility_operator(state: &mut State,
-                                utility_operation: &UtilityOperation) -> Result<ItemStruct> {
-
-    fn process_structure<F>(structure: &ItemStruct, processor: F) -> ItemStruct
-        where
-            F: FnOnce(&ItemStruct) -> ItemStruct {
-        processor(structure)
-    }
-
-    return match utility_operation {
-        UtilityOperation::Required(ident) | UtilityOperation::Optional(ident) => {
-            let structure = state.variables.get(ident.to_string().as_str());
-            match structure {
-                None => return generate_not_found_error(ident),
-                Some(structure) => {
-                    let processed_structure = match utility_operation {
-                        UtilityOperation::Optional(_) => process_structure(structure, process_optional),
-                        UtilityOperation::Required(_) => process_structure(structure, process_required)
-                    };
-                    Ok(processed_structure)
-                }
+                                utility_operation: &UtilityOperation) -> Result<Definition> {
+    match utility_operation {
+        UtilityOperation::Required(expression, selectors) => {
+            process_required(&process_type_expression(state, expression)?, selectors, &state.settings.nullable)
+        },
+        UtilityOperation::Optional(expression, selectors) => {
+            process_optional(&process_type_expression(state, expression)?, selectors, &state.settings.nullable)
+        },
+        UtilityOperation::Pick(expression, selectors) => {
+            process_pick(&process_type_expression(state, expression)?, selectors, &state.settings.nullable)
+        },
+        UtilityOperation::Omit(expression, selectors) => {
+            process_omit(&process_type_expression(state, expression)?, selectors, &state.settings.nullable)
+        },
+        UtilityOperation::Merge(expressions, conflict) => {
+            let mut definitions = Vec::new();
+            for expression in expressions {
+                definitions.push((expression, process_type_expression(state, expression)?));
             }
+            process_merge(&definitions, *conflict)
+        },
+        UtilityOperation::KeyOf(expression) => {
+            process_key_of(&process_type_expression(state, expression)?)
+        },
+        UtilityOperation::FieldType(expression, field) => {
+            process_field_type(&process_type_expression(state, expression)?, field)
+        },
+        UtilityOperation::Rename(expression, rules) => {
+            process_rename(&process_type_expression(state, expression)?, rules)
+        },
+        UtilityOperation::Wrap(expression, wrapper, selectors) => {
+            process_wrap(&process_type_expression(state, expression)?, wrapper, selectors, &state.settings.nullable)
+        },
+        UtilityOperation::Unwrap(expression, wrapper, selectors) => {
+            process_unwrap(&process_type_expression(state, expression)?, wrapper, selectors, &state.settings.nullable)
+        },
+        UtilityOperation::Map(expression, rules) => {
+            process_map(&process_type_expression(state, expression)?, rules)
+        },
+        UtilityOperation::DeepOptional(expression) => {
+            process_deep(state, expression, DeepOperation::Optional, None)
+        },
+        UtilityOperation::DeepRequired(expression) => {
+            process_deep(state, expression, DeepOperation::Required, None)
         }
     }
 }
@@ -42,7 +91,7 @@ pub fn process_utility_operator(state: &mut State,
 mod tests {
     use quote::ToTokens;
     use super::*;
-    use syn::parse_quote;
+    use syn::{ItemStruct, parse_quote};
 
     #[test]
     fn test_process_utility_operator() {
@@ -90,4 +139,50 @@ mod tests {
         };
         assert_eq!(actual.to_token_stream().to_string(), expected.to_token_stream().to_string());
     }
+
+    #[test]
+    fn test_process_utility_operator_pick() {
+        let mut state = State::new();
+        let input = parse_quote! { Pick(Test, field2) };
+
+        let structure = parse_quote! {
+            struct Test {
+                field1: FieldType,
+                field2: Option<FieldType>
+            }
+        };
+        state.variables.insert("Test".to_string(), structure);
+
+        let actual = process_utility_operator(&mut state, &input).unwrap();
+
+        let expected: ItemStruct = parse_quote! {
+            struct TestPick {
+                field2: Option<FieldType>
+            }
+        };
+        assert_eq!(actual.to_token_stream().to_string(), expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_utility_operator_omit() {
+        let mut state = State::new();
+        let input = parse_quote! { Omit(Test, field2) };
+
+        let structure = parse_quote! {
+            struct Test {
+                field1: FieldType,
+                field2: Option<FieldType>
+            }
+        };
+        state.variables.insert("Test".to_string(), structure);
+
+        let actual = process_utility_operator(&mut state, &input).unwrap();
+
+        let expected: ItemStruct = parse_quote! {
+            struct TestOmit {
+                field1: FieldType,
+            }
+        };
+        assert_eq!(actual.to_token_stream().to_string(), expected.to_token_stream().to_string());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,81 @@
+use syn::{GenericArgument, Path, PathArguments, Type};
+
+/// Whether `path` names the `Option` of the standard library, either as `Option` or through
+/// `std::option` or `core::option`. Types named `Option` in other modules, e.g. `foo::Option`,
+/// are not.
+pub fn is_option(path: &Path) -> bool {
+    let segments = segment_names(path);
+    match segments.as_slice() {
+        [name] => path.leading_colon.is_none() && name == "Option",
+        [root, module, name] => (root == "std" || root == "core") && module == "option" && name == "Option",
+        _ => false
+    }
+}
+
+/// The type wrapped by `ty` if it is an `Option` or one of the `nullable` wrappers, e.g. `T` for
+/// `Option<T>`. Wrappers are matched by their path as written, so `Maybe` doesn't match
+/// `other::Maybe`. Parentheses and invisible groups around `ty` are looked through.
+pub fn nullable_argument<'a>(ty: &'a Type, nullable: &[Path]) -> Option<&'a Type> {
+    let Type::Path(type_path) = without_parens(ty) else { return None };
+    if type_path.qself.is_some() {
+        return None
+    }
+    let path = &type_path.path;
+    let is_nullable = is_option(path) || nullable.iter().any(|wrapper| {
+        wrapper.leading_colon.is_some() == path.leading_colon.is_some() &&
+            segment_names(wrapper) == segment_names(path)
+    });
+    if !is_nullable || path.segments.iter().rev().skip(1).any(|segment| !segment.arguments.is_none()) {
+        return None
+    }
+    let PathArguments::AngleBracketed(arguments) = &path.segments.last()?.arguments else { return None };
+    arguments.args.iter().find_map(|argument| match argument {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None
+    })
+}
+
+/// `ty` without the parentheses, e.g. `(T)`, and invisible groups, e.g. a `$ty` passed on by
+/// `macro_rules`, around it.
+pub fn without_parens(ty: &Type) -> &Type {
+    match ty {
+        Type::Paren(paren) => without_parens(&paren.elem),
+        Type::Group(group) => without_parens(&group.elem),
+        _ => ty
+    }
+}
+
+fn segment_names(path: &Path) -> Vec<String> {
+    path.segments.iter().map(|segment| segment.ident.to_string()).collect()
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use quote::ToTokens;
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_is_option() {
+        assert!(is_option(&parse_quote!(Option)));
+        assert!(is_option(&parse_quote!(std::option::Option)));
+        assert!(is_option(&parse_quote!(::core::option::Option)));
+        assert!(!is_option(&parse_quote!(foo::Option)));
+        assert!(!is_option(&parse_quote!(::Option)));
+        assert!(!is_option(&parse_quote!(std::Option)));
+    }
+
+    #[test]
+    fn test_nullable_argument() {
+        let nullable: Vec<Path> = vec![parse_quote!(Maybe)];
+        let argument = |ty: Type| nullable_argument(&ty, &nullable).map(|ty| ty.to_token_stream().to_string());
+
+        assert_eq!(argument(parse_quote!(std::option::Option<u8>)), Some("u8".to_string()));
+        assert_eq!(argument(parse_quote!(Maybe<Vec<u8>>)), Some("Vec < u8 >".to_string()));
+        assert_eq!(argument(parse_quote!(other::Maybe<u8>)), None);
+        assert_eq!(argument(parse_quote!(models::Option<u8>)), None);
+        assert_eq!(argument(parse_quote!(Option)), None);
+        assert_eq!(argument(parse_quote!((Option<u8>))), Some("u8".to_string()));
+    }
+}
@@ -0,0 +1,151 @@
+use syn::{Fields, Path, Result};
+use crate::parser::composite_struct::field_selector::FieldSelector;
+use crate::processor::composite_struct::composite_operations::utility_operations::generics::retain_used_generics;
+use crate::processor::composite_struct::composite_operations::utility_operations::selectors::select_members;
+use crate::parser::definition::Definition;
+
+/// Drops the fields of a struct, or the variants of an enum, which are picked by `selectors`,
+/// keeping everything else in its declared order. Each named field or variant must exist on
+/// `definition`, and generic parameters which are only used by dropped fields are removed.
+pub fn process_omit(definition: &Definition, selectors: &[FieldSelector], nullable: &[Path]) -> Result<Definition> {
+    let selected = select_members(definition, selectors, nullable)?;
+    let mut new_definition = definition.clone();
+    new_definition.append_to_ident("Omit");
+    match &mut new_definition {
+        Definition::Struct(structure) => match &mut structure.fields {
+            Fields::Named(named) => named.named = named.named.clone().into_pairs().zip(selected)
+                .filter_map(|(pair, selected)| (!selected).then_some(pair))
+                .collect(),
+            Fields::Unnamed(unnamed) => unnamed.unnamed = unnamed.unnamed.clone().into_pairs().zip(selected)
+                .filter_map(|(pair, selected)| (!selected).then_some(pair))
+                .collect(),
+            Fields::Unit => {}
+        },
+        Definition::Enum(enumeration) => {
+            enumeration.variants = enumeration.variants.clone().into_pairs().zip(selected)
+                .filter_map(|(pair, selected)| (!selected).then_some(pair))
+                .collect();
+        },
+        Definition::Type(_) => {}
+    }
+    retain_used_generics(&mut new_definition);
+    Ok(new_definition)
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use quote::ToTokens;
+    use super::*;
+    use syn::{ItemEnum, ItemStruct, parse_quote};
+
+    #[test]
+    fn test_process_omit() {
+        let input: ItemStruct = parse_quote! {
+            struct Test {
+               id: u64,
+               #[doc = "display name"]
+               pub name: String,
+               created_at: u64
+            }
+        };
+        let fields: Vec<FieldSelector> = vec![parse_quote!(created_at), parse_quote!(id)];
+
+        let expected: ItemStruct = parse_quote! {
+            struct TestOmit {
+               #[doc = "display name"]
+               pub name: String,
+            }
+        };
+
+        let actual = process_omit(&input.into(), &fields, &[]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_omit_unknown_field() {
+        let input: ItemStruct = parse_quote! {
+            struct Test {
+               id: u64
+            }
+        };
+        let fields: Vec<FieldSelector> = vec![parse_quote!(created_at)];
+
+        let actual = process_omit(&input.into(), &fields, &[]).err().unwrap();
+        assert_eq!(actual.to_string(), "Field `created_at` does not exist on type `Test`");
+    }
+
+    #[test]
+    fn test_process_omit_selectors() {
+        let input: ItemStruct = parse_quote! {
+            struct User {
+               id: u64,
+               #[pii]
+               email: String,
+               addr_line1: String,
+               addr_city: String
+            }
+        };
+        let selectors: Vec<FieldSelector> = vec![parse_quote!(#[pii]), parse_quote!(addr_*), parse_quote!(!addr_city)];
+
+        let expected: ItemStruct = parse_quote! {
+            struct UserOmit {
+               id: u64,
+               addr_city: String
+            }
+        };
+
+        let actual = process_omit(&input.into(), &selectors, &[]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_omit_tuple_struct() {
+        let input: ItemStruct = parse_quote! {
+            struct Pair(u8, String, u8);
+        };
+        let selectors: Vec<FieldSelector> = vec![parse_quote!(type: u8)];
+
+        let actual = process_omit(&input.into(), &selectors, &[]).unwrap();
+        assert_eq!(actual.to_token_stream().to_string(), "struct PairOmit (String ,) ;");
+    }
+
+    #[test]
+    fn test_process_omit_enum() {
+        let input: ItemEnum = parse_quote! {
+            enum Shape {
+               Circle { radius: f64 },
+               Square(f64)
+            }
+        };
+        let variants: Vec<FieldSelector> = vec![parse_quote!(Circle)];
+
+        let expected: ItemEnum = parse_quote! {
+            enum ShapeOmit {
+               Square(f64)
+            }
+        };
+
+        let actual = process_omit(&input.into(), &variants, &[]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_omit_enum_unknown_variant() {
+        let input: ItemEnum = parse_quote! {
+            enum Shape {
+               Circle { radius: f64 }
+            }
+        };
+        let variants: Vec<FieldSelector> = vec![parse_quote!(radius)];
+
+        let actual = process_omit(&input.into(), &variants, &[]).err().unwrap();
+        assert_eq!(actual.to_string(), "Variant `radius` does not exist on enum `Shape`");
+    }
+}
@@ -1,31 +1,48 @@
-use proc_macro2::Ident;
-use quote::quote;
-use syn::{Fields, FieldsNamed, ItemStruct, token};
+use quote::ToTokens;
+use syn::{Field, parse_quote, Path, Result, Type};
+use syn::spanned::Spanned;
+use crate::parser::composite_struct::field_selector::FieldSelector;
+use crate::processor::composite_struct::composite_operations::utility_operations::nullable::{nullable_argument, without_parens};
+use crate::processor::composite_struct::composite_operations::utility_operations::selectors::select_fields;
+use crate::parser::definition::Definition;
+use crate::processor::errors::combine_errors;
 
-pub fn process_optional(structure: &ItemStruct) -> ItemStruct {
-    let mut new_struct = structure.clone();
+/// Wraps every field of `definition` which is picked by `selectors` in `Option`, unless it is
+/// already an `Option` or one of the `nullable` wrappers. For enums, this applies to the fields of
+/// every variant. Named, tuple and unit structs and variants keep their kind, and generics are
+/// kept as they are. Parentheses around field types are dropped, and fields whose type can't be
+/// an argument of `Option`, such as `[u8]` or `dyn Trait`, are an error.
+pub fn process_optional(definition: &Definition, selectors: &[FieldSelector], nullable: &[Path]) -> Result<Definition> {
+    let selected = select_fields(definition, selectors, nullable)?;
+    let mut new_definition = definition.clone();
+    new_definition.append_to_ident("Optional");
     // Wrap fields with Optional
+    let errors = new_definition.fields_mut().zip(selected)
+        .filter(|(_, selected)| *selected)
+        .filter_map(|(field, _)| optional_field(field, nullable).err())
+        .collect::<Vec<syn::Error>>();
+    combine_errors(errors)?;
+    Ok(new_definition)
+}
 
-    let fields = structure.fields.iter().map(|field| {
-        new_struct.ident = Ident::new(&format!("{}Optional", structure.ident), structure.ident.span());
-        let mut new_field = field.clone();
-        let field_type = &field.ty;
-        // If the field is not already an optional, wrap it in an optional
-        if let syn::Type::Path(type_path) = field_type {
-            if let Some(ident) = type_path.path.segments.first() {
-                let field_type = ident.ident.to_string();
-                if field_type != "Option" {
-                    new_field.ty = syn::parse2(quote! { Option<#type_path> }).unwrap();
-                }
-            }
-        }
-        new_field
-    }).collect();
-    new_struct.fields = Fields::Named(FieldsNamed {
-        brace_token: token::Brace::default(),
-        named: fields
-    });
-    new_struct
+fn optional_field(field: &mut Field, nullable: &[Path]) -> Result<()> {
+    // If the field is not already an optional, wrap it in an optional
+    if nullable_argument(&field.ty, nullable).is_some() {
+        return Ok(())
+    }
+    let ty = without_parens(&field.ty);
+    match ty {
+        Type::Path(_) | Type::Reference(_) | Type::Tuple(_) | Type::Array(_) | Type::BareFn(_) |
+        Type::Ptr(_) | Type::Macro(_) | Type::Never(_) => {
+            field.ty = parse_quote! { Option<#ty> };
+            Ok(())
+        },
+        // `Option` requires its argument to be sized
+        Type::Slice(_) | Type::TraitObject(_) => Err(syn::Error::new(ty.span(), format!(
+            "`{}` is unsized, so it can't be wrapped in `Option`", ty.to_token_stream()))),
+        _ => Err(syn::Error::new(ty.span(), format!(
+            "`{}` can't be wrapped in `Option`", ty.to_token_stream())))
+    }
 }
 
 
@@ -34,7 +51,7 @@ pub fn process_optional(structure: &ItemStruct) -> ItemStruct {
 mod tests {
     use quote::ToTokens;
     use super::*;
-    use syn::{parse_quote, parse_str};
+    use syn::{ItemEnum, ItemStruct, parse_quote};
 
     #[test]
     fn test_process_optional() {
@@ -50,7 +67,7 @@ mod tests {
             }
         };
 
-        let actual = process_optional(&input);
+        let actual = process_optional(&input.into(), &[], &[]).unwrap();
         assert_eq!(
             actual.to_token_stream().to_string(),
             expected.to_token_stream().to_string());
@@ -70,7 +87,7 @@ mod tests {
             }
         };
 
-        let actual = process_optional(&input);
+        let actual = process_optional(&input.into(), &[], &[]).unwrap();
         assert_eq!(
             actual.to_token_stream().to_string(),
             expected.to_token_stream().to_string());
@@ -90,9 +107,230 @@ mod tests {
             }
         };
 
-        let actual = process_optional(&input);
+        let actual = process_optional(&input.into(), &[], &[]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_optional_tuple_struct() {
+        let input: ItemStruct = parse_quote! {
+            pub struct Meters(pub f64, Option<u8>);
+        };
+
+        let expected: ItemStruct = parse_quote! {
+            pub struct MetersOptional(pub Option<f64>, Option<u8>);
+        };
+
+        let actual = process_optional(&input.into(), &[], &[]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_optional_unit_struct() {
+        let input: ItemStruct = parse_quote! {
+            struct Marker;
+        };
+
+        let expected: ItemStruct = parse_quote! {
+            struct MarkerOptional;
+        };
+
+        let actual = process_optional(&input.into(), &[], &[]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_optional_generic_type_param() {
+        let input: ItemStruct = parse_quote! {
+            struct Page<'a, T: Clone, const N: usize> where T: Debug {
+               items: [T; N],
+               current: T,
+               first: &'a T,
+               cursor: Option<T>
+            }
+        };
+
+        let expected: ItemStruct = parse_quote! {
+            struct PageOptional<'a, T: Clone, const N: usize> where T: Debug {
+               items: [T; N],
+               current: Option<T>,
+               first: &'a T,
+               cursor: Option<T>
+            }
+        };
+
+        let actual = process_optional(&input.into(), &[parse_quote!(type: T)], &[]).unwrap();
         assert_eq!(
             actual.to_token_stream().to_string(),
             expected.to_token_stream().to_string());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_process_optional_undeclared_type_param() {
+        let input: ItemStruct = parse_quote! {
+            struct Page<T> {
+               current: T
+            }
+        };
+
+        let actual = process_optional(&input.into(), &[parse_quote!(type: U)], &[]);
+        assert_eq!(
+            actual.err().unwrap().to_string(),
+            "`type: U` does not match any field of `Page`");
+    }
+
+    #[test]
+    fn test_process_optional_field_names() {
+        let input: ItemStruct = parse_quote! {
+            struct User {
+               id: u64,
+               email: String,
+               phone: String
+            }
+        };
+
+        let expected: ItemStruct = parse_quote! {
+            struct UserOptional {
+               id: u64,
+               email: Option<String>,
+               phone: Option<String>
+            }
+        };
+
+        let actual = process_optional(&input.into(), &[parse_quote!(email), parse_quote!(phone)], &[]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_optional_unknown_field() {
+        let input: ItemStruct = parse_quote! {
+            struct User {
+               id: u64
+            }
+        };
+
+        let actual = process_optional(&input.into(), &[parse_quote!(email)], &[]);
+        assert_eq!(actual.err().unwrap().to_string(), "Field `email` does not exist on type `User`");
+    }
+
+    #[test]
+    fn test_process_optional_enum() {
+        let input: ItemEnum = parse_quote! {
+            #[derive(Debug)]
+            pub enum Shape {
+               Circle { radius: f64, label: Option<String> },
+               Square(f64),
+               Empty = 3
+            }
+        };
+
+        let expected: ItemEnum = parse_quote! {
+            #[derive(Debug)]
+            pub enum ShapeOptional {
+               Circle { radius: Option<f64>, label: Option<String> },
+               Square(Option<f64>),
+               Empty = 3
+            }
+        };
+
+        let actual = process_optional(&input.into(), &[], &[]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_optional_option_paths() {
+        let input: ItemStruct = parse_quote! {
+            struct Test {
+               std: std::option::Option<u8>,
+               other: models::Option<u8>,
+               nickname: Maybe<String>
+            }
+        };
+
+        let expected: ItemStruct = parse_quote! {
+            struct TestOptional {
+               std: std::option::Option<u8>,
+               other: Option<models::Option<u8> >,
+               nickname: Maybe<String>
+            }
+        };
+
+        let actual = process_optional(&input.into(), &[], &[parse_quote!(Maybe)]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_optional_non_path_types() {
+        let input: ItemStruct = parse_quote! {
+            struct Test<'a> {
+               name: &'a str,
+               point: (u8, u8),
+               key: [u8; 32],
+               callback: fn(i32) -> i32,
+               handler: Box<dyn Fn()>,
+               raw: *const u8,
+               grouped: (Option<u8>),
+               parenthesized: (u8)
+            }
+        };
+
+        let expected: ItemStruct = parse_quote! {
+            struct TestOptional<'a> {
+               name: Option<&'a str>,
+               point: Option<(u8, u8)>,
+               key: Option<[u8; 32]>,
+               callback: Option<fn(i32) -> i32>,
+               handler: Option<Box<dyn Fn()> >,
+               raw: Option<*const u8>,
+               grouped: (Option<u8>),
+               parenthesized: Option<u8>
+            }
+        };
+
+        let actual = process_optional(&input.into(), &[], &[]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_optional_unsized_types() {
+        let input: ItemStruct = parse_quote! {
+            struct Test {
+               id: u8,
+               data: [u8]
+            }
+        };
+
+        let actual = process_optional(&input.into(), &[], &[]);
+        assert_eq!(
+            actual.err().unwrap().to_string(),
+            "`[u8]` is unsized, so it can't be wrapped in `Option`");
+    }
+
+    #[test]
+    fn test_process_optional_trait_object() {
+        let input: ItemStruct = parse_quote! {
+            struct Test {
+               handler: dyn Fn()
+            }
+        };
+
+        let actual = process_optional(&input.into(), &[parse_quote!(handler)], &[]);
+        assert_eq!(
+            actual.err().unwrap().to_string(),
+            "`dyn Fn ()` is unsized, so it can't be wrapped in `Option`");
+    }
+}
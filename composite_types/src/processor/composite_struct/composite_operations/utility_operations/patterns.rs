@@ -0,0 +1,122 @@
+use quote::ToTokens;
+use syn::{GenericArgument, PathArguments, ReturnType, Type};
+use crate::parser::composite_struct::map_rule::metavariable;
+
+/// Types bound to the metavariables of a pattern, in the order they were first matched.
+pub type Bindings = Vec<(String, Type)>;
+
+/// Whether `ty` has the shape of `pattern`, where `_` in the pattern matches any type and an
+/// elided lifetime matches any lifetime.
+pub fn type_matches(pattern: &Type, ty: &Type) -> bool {
+    match_type(pattern, ty, &mut Bindings::new())
+}
+
+/// Like [type_matches], where a metavariable such as `$T` additionally matches any type and binds
+/// it. A metavariable used more than once must match the same type every time.
+pub fn match_type(pattern: &Type, ty: &Type, bindings: &mut Bindings) -> bool {
+    if let Some(name) = metavariable(pattern) {
+        let tokens = ty.to_token_stream().to_string();
+        return match bindings.iter().find(|(bound, _)| bound == &name) {
+            Some((_, bound)) => bound.to_token_stream().to_string() == tokens,
+            None => {
+                bindings.push((name, ty.clone()));
+                true
+            }
+        }
+    }
+    match (pattern, ty) {
+        (Type::Infer(_), _) => true,
+        (Type::Paren(pattern), _) => match_type(&pattern.elem, ty, bindings),
+        (_, Type::Paren(ty)) => match_type(pattern, &ty.elem, bindings),
+        (Type::Group(pattern), _) => match_type(&pattern.elem, ty, bindings),
+        (_, Type::Group(ty)) => match_type(pattern, &ty.elem, bindings),
+        (Type::Path(pattern), Type::Path(ty)) => {
+            pattern.qself.is_none() && ty.qself.is_none() &&
+                pattern.path.leading_colon.is_some() == ty.path.leading_colon.is_some() &&
+                pattern.path.segments.len() == ty.path.segments.len() &&
+                pattern.path.segments.iter().zip(ty.path.segments.iter()).all(|(pattern, segment)| {
+                    pattern.ident == segment.ident && match_arguments(&pattern.arguments, &segment.arguments, bindings)
+                })
+        },
+        (Type::Reference(pattern), Type::Reference(ty)) => {
+            pattern.mutability.is_some() == ty.mutability.is_some() &&
+                pattern.lifetime.as_ref().map_or(true, |lifetime| {
+                    lifetime.ident == "_" || Some(lifetime) == ty.lifetime.as_ref()
+                }) &&
+                match_type(&pattern.elem, &ty.elem, bindings)
+        },
+        (Type::Tuple(pattern), Type::Tuple(ty)) => {
+            pattern.elems.len() == ty.elems.len() &&
+                pattern.elems.iter().zip(ty.elems.iter()).all(|(pattern, ty)| match_type(pattern, ty, bindings))
+        },
+        (Type::Slice(pattern), Type::Slice(ty)) => match_type(&pattern.elem, &ty.elem, bindings),
+        (Type::Array(pattern), Type::Array(ty)) => {
+            match_type(&pattern.elem, &ty.elem, bindings) &&
+                pattern.len.to_token_stream().to_string() == ty.len.to_token_stream().to_string()
+        },
+        (Type::Ptr(pattern), Type::Ptr(ty)) => {
+            pattern.mutability.is_some() == ty.mutability.is_some() && match_type(&pattern.elem, &ty.elem, bindings)
+        },
+        _ => pattern.to_token_stream().to_string() == ty.to_token_stream().to_string()
+    }
+}
+
+fn match_arguments(pattern: &PathArguments, arguments: &PathArguments, bindings: &mut Bindings) -> bool {
+    match (pattern, arguments) {
+        (PathArguments::None, PathArguments::None) => true,
+        (PathArguments::AngleBracketed(pattern), PathArguments::AngleBracketed(arguments)) => {
+            pattern.args.len() == arguments.args.len() &&
+                pattern.args.iter().zip(arguments.args.iter()).all(|(pattern, argument)| match (pattern, argument) {
+                    (GenericArgument::Type(pattern), GenericArgument::Type(ty)) => match_type(pattern, ty, bindings),
+                    (GenericArgument::Type(Type::Infer(_)), _) => true,
+                    (GenericArgument::Lifetime(pattern), GenericArgument::Lifetime(_)) if pattern.ident == "_" => true,
+                    (pattern, argument) => pattern.to_token_stream().to_string() == argument.to_token_stream().to_string()
+                })
+        },
+        (PathArguments::Parenthesized(pattern), PathArguments::Parenthesized(arguments)) => {
+            pattern.inputs.len() == arguments.inputs.len() &&
+                pattern.inputs.iter().zip(arguments.inputs.iter()).all(|(pattern, ty)| match_type(pattern, ty, bindings)) &&
+                match (&pattern.output, &arguments.output) {
+                    (ReturnType::Default, ReturnType::Default) => true,
+                    (ReturnType::Type(_, pattern), ReturnType::Type(_, ty)) => match_type(pattern, ty, bindings),
+                    _ => false
+                }
+        },
+        _ => false
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_type_matches() {
+        assert!(type_matches(&parse_quote!(Option<_>), &parse_quote!(Option<Vec<u8>>)));
+        assert!(type_matches(&parse_quote!(&'_ str), &parse_quote!(&'a str)));
+        assert!(type_matches(&parse_quote!(&str), &parse_quote!(&'a str)));
+        assert!(!type_matches(&parse_quote!(&'static str), &parse_quote!(&'a str)));
+        assert!(!type_matches(&parse_quote!(Option<_>), &parse_quote!(std::option::Option<u8>)));
+    }
+
+    #[test]
+    fn test_match_type_bindings() {
+        let mut bindings = Bindings::new();
+        let pattern: Type = parse_quote!(HashMap<__compose_type_metavariable_K, Vec<__compose_type_metavariable_V>>);
+
+        assert!(match_type(&pattern, &parse_quote!(HashMap<String, Vec<(u8, u8)>>), &mut bindings));
+        assert_eq!(bindings.len(), 2);
+        assert_eq!(bindings[0].0, "K");
+        assert_eq!(bindings[1].1.to_token_stream().to_string(), "(u8 , u8)");
+    }
+
+    #[test]
+    fn test_match_type_repeated_metavariable() {
+        let pattern: Type = parse_quote!((__compose_type_metavariable_T, __compose_type_metavariable_T));
+
+        assert!(match_type(&pattern, &parse_quote!((u8, u8)), &mut Bindings::new()));
+        assert!(!match_type(&pattern, &parse_quote!((u8, u16)), &mut Bindings::new()));
+    }
+}
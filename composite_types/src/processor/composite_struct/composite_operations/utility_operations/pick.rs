@@ -0,0 +1,163 @@
+use syn::{Fields, Path, Result};
+use crate::parser::composite_struct::field_selector::FieldSelector;
+use crate::processor::composite_struct::composite_operations::utility_operations::generics::retain_used_generics;
+use crate::processor::composite_struct::composite_operations::utility_operations::selectors::select_members;
+use crate::parser::definition::Definition;
+
+/// Keeps only the fields of a struct, or the variants of an enum, which are picked by `selectors`,
+/// in their declared order. Each named field or variant must exist on `definition`, and generic
+/// parameters which are only used by dropped fields are removed.
+pub fn process_pick(definition: &Definition, selectors: &[FieldSelector], nullable: &[Path]) -> Result<Definition> {
+    let selected = select_members(definition, selectors, nullable)?;
+    let mut new_definition = definition.clone();
+    new_definition.append_to_ident("Pick");
+    match &mut new_definition {
+        Definition::Struct(structure) => match &mut structure.fields {
+            Fields::Named(named) => named.named = named.named.clone().into_pairs().zip(selected)
+                .filter_map(|(pair, selected)| selected.then_some(pair))
+                .collect(),
+            Fields::Unnamed(unnamed) => unnamed.unnamed = unnamed.unnamed.clone().into_pairs().zip(selected)
+                .filter_map(|(pair, selected)| selected.then_some(pair))
+                .collect(),
+            Fields::Unit => {}
+        },
+        Definition::Enum(enumeration) => {
+            enumeration.variants = enumeration.variants.clone().into_pairs().zip(selected)
+                .filter_map(|(pair, selected)| selected.then_some(pair))
+                .collect();
+        },
+        Definition::Type(_) => {}
+    }
+    retain_used_generics(&mut new_definition);
+    Ok(new_definition)
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use quote::ToTokens;
+    use super::*;
+    use syn::{ItemEnum, ItemStruct, parse_quote};
+
+    #[test]
+    fn test_process_pick() {
+        let input: ItemStruct = parse_quote! {
+            struct Test {
+               #[doc = "identifier"]
+               pub id: u64,
+               name: String,
+               email: Option<String>
+            }
+        };
+        let fields: Vec<FieldSelector> = vec![parse_quote!(email), parse_quote!(id)];
+
+        let expected: ItemStruct = parse_quote! {
+            struct TestPick {
+               #[doc = "identifier"]
+               pub id: u64,
+               email: Option<String>
+            }
+        };
+
+        let actual = process_pick(&input.into(), &fields, &[]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_pick_unknown_field() {
+        let input: ItemStruct = parse_quote! {
+            struct Test {
+               id: u64
+            }
+        };
+        let fields: Vec<FieldSelector> = vec![parse_quote!(id), parse_quote!(name)];
+
+        let actual = process_pick(&input.into(), &fields, &[]).err().unwrap();
+        assert_eq!(actual.to_string(), "Field `name` does not exist on type `Test`");
+    }
+
+    #[test]
+    fn test_process_pick_generic() {
+        let input: ItemStruct = parse_quote! {
+            struct Page<'a, T: Clone, U> where U: Default {
+               items: Vec<T>,
+               cursor: &'a U,
+               total: usize
+            }
+        };
+        let fields: Vec<FieldSelector> = vec![parse_quote!(items), parse_quote!(total)];
+
+        let expected: ItemStruct = parse_quote! {
+            struct PagePick<T: Clone> {
+               items: Vec<T>,
+               total: usize
+            }
+        };
+
+        let actual = process_pick(&input.into(), &fields, &[]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_pick_type_pattern() {
+        let input: ItemStruct = parse_quote! {
+            struct User {
+               id: u64,
+               email: Option<String>,
+               phone: Option<String>
+            }
+        };
+        let selectors: Vec<FieldSelector> = vec![parse_quote!(type: Option<_>)];
+
+        let expected: ItemStruct = parse_quote! {
+            struct UserPick {
+               email: Option<String>,
+               phone: Option<String>
+            }
+        };
+
+        let actual = process_pick(&input.into(), &selectors, &[]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_pick_tuple_struct() {
+        let input: ItemStruct = parse_quote! {
+            struct Pair(u8, String, u8);
+        };
+        let selectors: Vec<FieldSelector> = vec![parse_quote!(type: u8)];
+
+        let actual = process_pick(&input.into(), &selectors, &[]).unwrap();
+        assert_eq!(actual.to_token_stream().to_string(), "struct PairPick (u8 , u8) ;");
+    }
+
+    #[test]
+    fn test_process_pick_enum() {
+        let input: ItemEnum = parse_quote! {
+            enum Shape<T> {
+               Circle { radius: f64 },
+               Square(f64),
+               Custom(T)
+            }
+        };
+        let variants: Vec<FieldSelector> = vec![parse_quote!(Square), parse_quote!(Circle)];
+
+        let expected: ItemEnum = parse_quote! {
+            enum ShapePick {
+               Circle { radius: f64 },
+               Square(f64),
+            }
+        };
+
+        let actual = process_pick(&input.into(), &variants, &[]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+}
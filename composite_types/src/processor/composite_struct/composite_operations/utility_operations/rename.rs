@@ -0,0 +1,348 @@
+use proc_macro2::{Ident, Span};
+use syn::{Fields, ItemImpl, parse_quote, Path, PathArguments, Result, Type};
+use syn::ext::IdentExt;
+use crate::parser::composite_struct::rename_rule::{NamingConvention, RenameRule};
+use crate::parser::composite_struct::type_expression::TypeExpression;
+use crate::parser::definition::Definition;
+use crate::processor::composite_struct::composite_operations::utility_operations::helpers::validate_fields_exist;
+use crate::processor::errors::{article, combine_errors};
+
+/// Renames the named fields of a struct. Fields listed as `old => new` take the new name, and
+/// every other field has the remaining rules applied to its name in the order they are listed.
+/// Types, attributes, visibility and generics of the fields are kept. Structs renamed to a
+/// convention other than `snake_case` are marked `#[allow(non_snake_case)]`.
+pub fn process_rename(definition: &Definition, rules: &[RenameRule]) -> Result<Definition> {
+    let structure = match definition {
+        Definition::Struct(structure) => structure,
+        _ => return Err(syn::Error::new(definition.ident().span(), format!(
+            "Rename can only be applied to structs, but `{}` is {} {}",
+            definition.ident(), article(definition.kind()), definition.kind())))
+    };
+    if let Fields::Unnamed(_) = structure.fields {
+        return Err(syn::Error::new(definition.ident().span(), format!(
+            "Rename can only be applied to structs with named fields, but `{}` is a tuple struct", definition.ident())));
+    }
+    let explicit = rules.iter().filter_map(|rule| match rule {
+        RenameRule::Field(old, new) => Some((old, new)),
+        _ => None
+    }).collect::<Vec<(&Ident, &Ident)>>();
+    validate_fields_exist(definition, &explicit.iter().map(|(old, _)| (*old).clone()).collect::<Vec<Ident>>())?;
+
+    let mut errors = Vec::new();
+    for (index, (old, _)) in explicit.iter().enumerate() {
+        if explicit[..index].iter().any(|(previous, _)| previous == old) {
+            errors.push(syn::Error::new(old.span(), format!("Field `{}` is renamed more than once", old)));
+        }
+    }
+
+    let mut new_definition = definition.clone();
+    new_definition.append_to_ident("Rename");
+    let mut renamed: Vec<(Ident, Ident)> = Vec::new();
+    for field in new_definition.fields_mut() {
+        let Some(ident) = field.ident.clone() else { continue };
+        let new_ident = match explicit.iter().find(|(old, _)| **old == ident) {
+            Some((_, new)) => (*new).clone(),
+            None => match to_ident(&apply_rules(&ident.unraw().to_string(), rules), ident.span()) {
+                Some(new_ident) => new_ident,
+                None => {
+                    errors.push(syn::Error::new(ident.span(), format!(
+                        "Renaming `{}` produces `{}`, which is not a valid field name",
+                        ident, apply_rules(&ident.unraw().to_string(), rules))));
+                    continue
+                }
+            }
+        };
+        match renamed.iter().find(|(_, existing)| existing.unraw() == new_ident.unraw()) {
+            Some((existing, _)) => errors.push(syn::Error::new(ident.span(), format!(
+                "Fields `{}` and `{}` would both be renamed to `{}`", existing, ident, new_ident))),
+            None => renamed.push((ident, new_ident.clone()))
+        }
+        field.ident = Some(new_ident);
+    }
+    combine_errors(errors)?;
+    // Other conventions produce names like `createdAt`, which rustc would warn about
+    let converts_case = rules.iter()
+        .any(|rule| matches!(rule, RenameRule::Convention(convention) if *convention != NamingConvention::Snake));
+    if let (true, Definition::Struct(structure)) = (converts_case, &mut new_definition) {
+        structure.attrs.push(parse_quote!(#[allow(non_snake_case)]));
+    }
+    Ok(new_definition)
+}
+
+/// `From` conversions in both directions between the type named by `expression` and `renamed`,
+/// which was produced from its `definition` by [process_rename]. Operations used as the operand
+/// are not emitted as a type, so they have no conversions.
+pub fn generate_rename_conversions(expression: &TypeExpression, definition: &Definition, renamed: &Definition) -> Vec<ItemImpl> {
+    let Some((source, constructor)) = conversion_source(expression, definition) else { return Vec::new() };
+    let name = renamed.ident();
+    let old = definition.fields().filter_map(|field| field.ident.as_ref()).collect::<Vec<&Ident>>();
+    let new = renamed.fields().filter_map(|field| field.ident.as_ref()).collect::<Vec<&Ident>>();
+    let (impl_generics, type_generics, where_clause) = renamed.generics().split_for_impl();
+    vec![
+        parse_quote! {
+            impl #impl_generics ::core::convert::From<#source> for #name #type_generics #where_clause {
+                fn from(value: #source) -> Self {
+                    #name {
+                        #(#new: value.#old),*
+                    }
+                }
+            }
+        },
+        parse_quote! {
+            impl #impl_generics ::core::convert::From<#name #type_generics> for #source #where_clause {
+                fn from(value: #name #type_generics) -> Self {
+                    #constructor {
+                        #(#old: value.#new),*
+                    }
+                }
+            }
+        },
+    ]
+}
+
+/// Type named by `expression` in conversions from and to `definition`, and the path its values
+/// are constructed with, e.g. `Page<User>` and `Page`. Operations have neither.
+pub fn conversion_source(expression: &TypeExpression, definition: &Definition) -> Option<(Type, Path)> {
+    let (_, definition_generics, _) = definition.generics().split_for_impl();
+    match expression {
+        TypeExpression::Ident(ident) => Some((parse_quote!(#ident #definition_generics), parse_quote!(#ident))),
+        TypeExpression::Generic(ident, arguments) => Some((parse_quote!(#ident #arguments), parse_quote!(#ident))),
+        TypeExpression::Path(path) => {
+            let mut constructor = path.clone();
+            let last = constructor.segments.last_mut().unwrap();
+            match last.arguments {
+                PathArguments::None => Some((parse_quote!(#path #definition_generics), constructor)),
+                _ => {
+                    last.arguments = PathArguments::None;
+                    Some((parse_quote!(#path), constructor))
+                }
+            }
+        },
+        TypeExpression::UtilityOp(_) => None
+    }
+}
+
+fn apply_rules(name: &str, rules: &[RenameRule]) -> String {
+    rules.iter().fold(name.to_string(), |name, rule| match rule {
+        RenameRule::Field(_, _) => name,
+        RenameRule::Convention(convention) => convert_case(&name, *convention),
+        RenameRule::Prefix(prefix) => format!("{}{}", prefix.value(), name),
+        RenameRule::Suffix(suffix) => format!("{}{}", name, suffix.value())
+    })
+}
+
+/// Keywords such as `type` become raw identifiers, and names which can't be identifiers at all
+/// are rejected.
+fn to_ident(name: &str, span: Span) -> Option<Ident> {
+    let mut ident = syn::parse_str::<Ident>(name)
+        .or_else(|_| syn::parse_str::<Ident>(&format!("r#{}", name)))
+        .ok()?;
+    ident.set_span(span);
+    Some(ident)
+}
+
+/// Splits `name` into lowercase words at underscores and at lowercase to uppercase boundaries,
+/// then joins them in `convention`.
+fn convert_case(name: &str, convention: NamingConvention) -> String {
+    let mut words: Vec<String> = Vec::new();
+    let mut previous: Option<char> = None;
+    for character in name.chars() {
+        if character == '_' {
+            words.push(String::new());
+        } else {
+            let boundary = character.is_uppercase() && previous.is_some_and(|p| p.is_lowercase() || p.is_ascii_digit());
+            match words.last_mut() {
+                Some(word) if !boundary => word.extend(character.to_lowercase()),
+                _ => words.push(character.to_lowercase().collect())
+            }
+        }
+        previous = Some(character);
+    }
+    let words = words.into_iter().filter(|word| !word.is_empty()).collect::<Vec<String>>();
+    let capitalize = |word: &String| {
+        let mut characters = word.chars();
+        match characters.next() {
+            Some(first) => first.to_uppercase().chain(characters).collect::<String>(),
+            None => String::new()
+        }
+    };
+    match convention {
+        NamingConvention::LowerCamel => words.iter().enumerate()
+            .map(|(index, word)| if index == 0 { word.clone() } else { capitalize(word) })
+            .collect(),
+        NamingConvention::UpperCamel => words.iter().map(capitalize).collect(),
+        NamingConvention::Snake => words.join("_"),
+        NamingConvention::ScreamingSnake => words.join("_").to_uppercase()
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use quote::ToTokens;
+    use super::*;
+    use syn::{ItemEnum, ItemStruct};
+
+    #[test]
+    fn test_process_rename() {
+        let input: ItemStruct = parse_quote! {
+            pub struct User {
+               pub id: u64,
+               #[doc = "display name"]
+               name: String,
+               created_at: u64
+            }
+        };
+        let rules: Vec<RenameRule> = vec![parse_quote!(name => display_name), parse_quote!(camelCase)];
+
+        let expected: ItemStruct = parse_quote! {
+            #[allow(non_snake_case)]
+            pub struct UserRename {
+               pub id: u64,
+               #[doc = "display name"]
+               display_name: String,
+               createdAt: u64
+            }
+        };
+
+        let actual = process_rename(&input.into(), &rules).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_rename_affixes() {
+        let input: ItemStruct = parse_quote! {
+            struct User {
+               id: u64,
+               kind: String
+            }
+        };
+        let rules: Vec<RenameRule> = vec![parse_quote!(prefix = "ty"), parse_quote!(suffix = "_raw")];
+
+        let expected: ItemStruct = parse_quote! {
+            struct UserRename {
+               tyid_raw: u64,
+               tykind_raw: String
+            }
+        };
+
+        let actual = process_rename(&input.into(), &rules).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_rename_snake_case() {
+        let input: ItemStruct = parse_quote! {
+            #[derive(Debug)]
+            struct User {
+               userId: u64
+            }
+        };
+
+        let snake = process_rename(&input.clone().into(), &[parse_quote!(snake_case)]).unwrap();
+        let screaming = process_rename(&input.into(), &[parse_quote!(SCREAMING_SNAKE_CASE)]).unwrap();
+        let Definition::Struct(snake) = snake else { panic!("Expected a struct") };
+        let Definition::Struct(screaming) = screaming else { panic!("Expected a struct") };
+        assert_eq!(snake.attrs.len(), 1);
+        assert_eq!(
+            screaming.attrs.iter().map(|attr| attr.to_token_stream().to_string()).collect::<Vec<String>>(),
+            vec!["# [derive (Debug)]", "# [allow (non_snake_case)]"]);
+    }
+
+    #[test]
+    fn test_process_rename_keyword() {
+        let input: ItemStruct = parse_quote! {
+            struct User {
+               TYPE: String
+            }
+        };
+
+        let actual = process_rename(&input.into(), &[parse_quote!(snake_case)]).unwrap();
+        assert_eq!(actual.fields().next().unwrap().ident.as_ref().unwrap().to_string(), "r#type");
+    }
+
+    #[test]
+    fn test_process_rename_collision() {
+        let input: ItemStruct = parse_quote! {
+            struct User {
+               created_at: u64,
+               createdAt: u64
+            }
+        };
+
+        let actual = process_rename(&input.into(), &[parse_quote!(snake_case)]);
+        assert_eq!(
+            actual.err().unwrap().to_string(),
+            "Fields `created_at` and `createdAt` would both be renamed to `created_at`");
+    }
+
+    #[test]
+    fn test_process_rename_invalid_name() {
+        let input: ItemStruct = parse_quote! {
+            struct User {
+               id: u64
+            }
+        };
+
+        let actual = process_rename(&input.into(), &[parse_quote!(prefix = "1")]);
+        assert_eq!(
+            actual.err().unwrap().to_string(),
+            "Renaming `id` produces `1id`, which is not a valid field name");
+    }
+
+    #[test]
+    fn test_process_rename_enum() {
+        let input: ItemEnum = parse_quote! {
+            enum Shape {
+               Circle { radius: f64 }
+            }
+        };
+
+        let actual = process_rename(&input.into(), &[parse_quote!(camelCase)]);
+        assert_eq!(
+            actual.err().unwrap().to_string(),
+            "Rename can only be applied to structs, but `Shape` is an enum");
+    }
+
+    #[test]
+    fn test_generate_rename_conversions() {
+        let input: ItemStruct = parse_quote! {
+            struct Page<T> {
+               items: Vec<T>,
+               total_count: usize
+            }
+        };
+        let definition: Definition = input.into();
+        let mut renamed = process_rename(&definition, &[parse_quote!(camelCase)]).unwrap();
+        renamed.set_ident(parse_quote!(PageView));
+
+        let expected: ItemImpl = parse_quote! {
+            impl<T> ::core::convert::From<PageView<T> > for Page<T> {
+                fn from(value: PageView<T>) -> Self {
+                    Page {
+                        items: value.items,
+                        total_count: value.totalCount
+                    }
+                }
+            }
+        };
+
+        let actual = generate_rename_conversions(&parse_quote!(Page), &definition, &renamed);
+        assert_eq!(actual.len(), 2);
+        assert_eq!(
+            actual[1].to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_convert_case() {
+        assert_eq!(convert_case("created_at", NamingConvention::LowerCamel), "createdAt");
+        assert_eq!(convert_case("created_at", NamingConvention::UpperCamel), "CreatedAt");
+        assert_eq!(convert_case("createdAt", NamingConvention::Snake), "created_at");
+        assert_eq!(convert_case("_private_id", NamingConvention::ScreamingSnake), "PRIVATE_ID");
+    }
+}
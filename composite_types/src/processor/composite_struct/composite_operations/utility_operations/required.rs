@@ -1,38 +1,28 @@
-use proc_macro2::Ident;
-use quote::{quote, ToTokens};
-use syn::{Fields, FieldsNamed, ItemStruct, parse_quote, token};
-use crate::processor::composite_struct::composite_operations::utility_operations::helpers::get_first_generic_type_arg;
-
-pub fn process_required(structure: &ItemStruct) -> ItemStruct {
-    let mut new_struct = structure.clone();
-    new_struct.ident = Ident::new(&format!("{}Required", structure.ident), structure.ident.span());
-    let fields = structure.fields.iter().map(|field| {
-        let mut new_field = field.clone();
-        if let syn::Type::Path(ref type_path) = new_field.ty {
-
-            let field_type =  if let Some(ident) = type_path.path.segments.first() {
-                ident.ident.to_string()
-            } else { return new_field };
-
-            if field_type != "Option" { return new_field }
-
-            let new_type = get_first_generic_type_arg(&new_field);
-            // promote first generic argument to `Option` as the type argument
-            if let Some(new_type) = new_type {
-                new_field.ty = parse_quote! {
-                    #new_type
-                };
-            }
-        }
-        new_field
-    }).collect();
-    new_struct.fields = Fields::Named(FieldsNamed {
-        brace_token: token::Brace::default(),
-        named: fields
-    });
-    new_struct
+use syn::{Field, Path, Result};
+use crate::parser::composite_struct::field_selector::FieldSelector;
+use crate::processor::composite_struct::composite_operations::utility_operations::nullable::nullable_argument;
+use crate::processor::composite_struct::composite_operations::utility_operations::selectors::select_fields;
+use crate::parser::definition::Definition;
+
+/// Unwraps every field of `definition` which is picked by `selectors` and is an `Option` or one
+/// of the `nullable` wrappers. For enums, this applies to the fields of every variant. Named,
+/// tuple and unit structs and variants keep their kind, and generics are kept as they are.
+pub fn process_required(definition: &Definition, selectors: &[FieldSelector], nullable: &[Path]) -> Result<Definition> {
+    let selected = select_fields(definition, selectors, nullable)?;
+    let mut new_definition = definition.clone();
+    new_definition.append_to_ident("Required");
+    new_definition.fields_mut().zip(selected)
+        .filter(|(_, selected)| *selected)
+        .for_each(|(field, _)| require_field(field, nullable));
+    Ok(new_definition)
 }
 
+fn require_field(field: &mut Field, nullable: &[Path]) {
+    // promote the type argument of `Option` to the field type
+    if let Some(argument) = nullable_argument(&field.ty, nullable) {
+        field.ty = argument.clone();
+    }
+}
 
 
 // unit tests
@@ -40,7 +30,7 @@ pub fn process_required(structure: &ItemStruct) -> ItemStruct {
 mod tests {
     use quote::ToTokens;
     use super::*;
-    use syn::{parse_quote, parse_str};
+    use syn::{ItemEnum, ItemStruct, parse_quote};
 
     #[test]
     fn test_process_required() {
@@ -56,7 +46,7 @@ mod tests {
             }
         };
 
-        let actual = process_required(&input);
+        let actual = process_required(&input.into(), &[], &[]).unwrap();
         assert_eq!(
             actual.to_token_stream().to_string(),
             expected.to_token_stream().to_string());
@@ -76,7 +66,7 @@ mod tests {
             }
         };
 
-        let actual = process_required( &input);
+        let actual = process_required(&input.into(), &[], &[]).unwrap();
         assert_eq!(
             actual.to_token_stream().to_string(),
             expected.to_token_stream().to_string()
@@ -97,9 +87,207 @@ mod tests {
             }
         };
 
-        let actual = process_required(&input);
+        let actual = process_required(&input.into(), &[], &[]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_required_tuple_struct() {
+        let input: ItemStruct = parse_quote! {
+            pub struct Meters(pub Option<f64>, u8);
+        };
+
+        let expected: ItemStruct = parse_quote! {
+            pub struct MetersRequired(pub f64, u8);
+        };
+
+        let actual = process_required(&input.into(), &[], &[]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_required_unit_struct() {
+        let input: ItemStruct = parse_quote! {
+            struct Marker;
+        };
+
+        let expected: ItemStruct = parse_quote! {
+            struct MarkerRequired;
+        };
+
+        let actual = process_required(&input.into(), &[], &[]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_required_generic_type_param() {
+        let input: ItemStruct = parse_quote! {
+            struct Page<'a, T: Clone> where T: Debug {
+               current: Option<T>,
+               first: &'a T,
+               total: Option<usize>
+            }
+        };
+
+        let expected: ItemStruct = parse_quote! {
+            struct PageRequired<'a, T: Clone> where T: Debug {
+               current: T,
+               first: &'a T,
+               total: Option<usize>
+            }
+        };
+
+        let actual = process_required(&input.into(), &[parse_quote!(type: T)], &[]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_required_field_names() {
+        let input: ItemStruct = parse_quote! {
+            struct User {
+               id: Option<u64>,
+               email: Option<String>,
+               phone: Option<String>
+            }
+        };
+
+        let expected: ItemStruct = parse_quote! {
+            struct UserRequired {
+               id: Option<u64>,
+               email: String,
+               phone: Option<String>
+            }
+        };
+
+        let actual = process_required(&input.into(), &[parse_quote!(email)], &[]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_required_enum() {
+        let input: ItemEnum = parse_quote! {
+            enum Event<T> {
+               Created { id: Option<u64>, payload: Option<T> },
+               Deleted(Option<u64>),
+               #[doc = "no payload"]
+               Cleared
+            }
+        };
+
+        let expected: ItemEnum = parse_quote! {
+            enum EventRequired<T> {
+               Created { id: Option<u64>, payload: T },
+               Deleted(Option<u64>),
+               #[doc = "no payload"]
+               Cleared
+            }
+        };
+
+        let actual = process_required(&input.into(), &[parse_quote!(type: T)], &[]).unwrap();
         assert_eq!(
             actual.to_token_stream().to_string(),
             expected.to_token_stream().to_string());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_process_required_option_paths() {
+        let input: ItemStruct = parse_quote! {
+            struct Test {
+               std: std::option::Option<u8>,
+               core: ::core::option::Option<u8>,
+               other: models::Option<u8>
+            }
+        };
+
+        let expected: ItemStruct = parse_quote! {
+            struct TestRequired {
+               std: u8,
+               core: u8,
+               other: models::Option<u8>
+            }
+        };
+
+        let actual = process_required(&input.into(), &[], &[]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_required_nullable_wrapper() {
+        let input: ItemStruct = parse_quote! {
+            struct Test {
+               nickname: Maybe<String>,
+               email: Nullable<String>,
+               phone: Option<String>
+            }
+        };
+
+        let expected: ItemStruct = parse_quote! {
+            struct TestRequired {
+               nickname: String,
+               email: Nullable<String>,
+               phone: String
+            }
+        };
+
+        let actual = process_required(&input.into(), &[], &[parse_quote!(Maybe)]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_required_custom_nullable_selector() {
+        let input: ItemStruct = parse_quote! {
+            struct Test {
+               nickname: Maybe<String>,
+               age: Maybe<u8>
+            }
+        };
+
+        let expected: ItemStruct = parse_quote! {
+            struct TestRequired {
+               nickname: String,
+               age: Maybe<u8>
+            }
+        };
+
+        let actual = process_required(&input.into(), &[parse_quote!(type: String)], &[parse_quote!(Maybe)]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_required_parenthesized() {
+        let input: ItemStruct = parse_quote! {
+            struct Test<'a> {
+               name: (Option<&'a str>),
+               point: (u8, u8)
+            }
+        };
+
+        let expected: ItemStruct = parse_quote! {
+            struct TestRequired<'a> {
+               name: &'a str,
+               point: (u8, u8)
+            }
+        };
+
+        let actual = process_required(&input.into(), &[], &[]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+}
@@ -0,0 +1,331 @@
+use proc_macro2::Ident;
+use quote::ToTokens;
+use syn::{Attribute, Path, Result, Type};
+use syn::ext::IdentExt;
+use crate::parser::composite_struct::field_selector::FieldSelector;
+use crate::parser::definition::Definition;
+use crate::processor::composite_struct::composite_operations::utility_operations::helpers::validate_fields_exist;
+use crate::processor::composite_struct::composite_operations::utility_operations::patterns::type_matches;
+use crate::processor::composite_struct::composite_operations::utility_operations::nullable::nullable_argument;
+use crate::processor::errors::combine_errors;
+
+/// A field, or a variant of an enum, which selectors are matched against.
+struct Candidate<'a> {
+    name: Option<&'a Ident>,
+    attrs: Vec<&'a Attribute>,
+    types: Vec<&'a Type>,
+}
+
+/// Decides for each field of `definition`, in the order of [Definition::fields], whether it is
+/// selected by `selectors`. For enums, every selector except `type: T` matches the variant the
+/// field belongs to. `type: T` also matches fields of `Option<T>` or of one of the `nullable`
+/// wrappers around `T`.
+pub fn select_fields(definition: &Definition, selectors: &[FieldSelector], nullable: &[Path]) -> Result<Vec<bool>> {
+    let candidates = match definition {
+        Definition::Struct(structure) => structure.fields.iter().map(|field| Candidate {
+            name: field.ident.as_ref(),
+            attrs: field.attrs.iter().collect(),
+            types: vec![&field.ty]
+        }).collect(),
+        Definition::Enum(enumeration) => enumeration.variants.iter().flat_map(|variant| {
+            variant.fields.iter().map(move |field| Candidate {
+                name: Some(&variant.ident),
+                attrs: variant.attrs.iter().chain(field.attrs.iter()).collect(),
+                types: vec![&field.ty]
+            })
+        }).collect(),
+        Definition::Type(_) => Vec::new()
+    };
+    select(definition, &candidates, selectors, nullable)
+}
+
+/// Decides for each field of a struct, or each variant of an enum, whether it is selected by
+/// `selectors`. A variant matches `type: T` when any of its fields does.
+pub fn select_members(definition: &Definition, selectors: &[FieldSelector], nullable: &[Path]) -> Result<Vec<bool>> {
+    let candidates = match definition {
+        Definition::Struct(_) => return select_fields(definition, selectors, nullable),
+        Definition::Enum(enumeration) => enumeration.variants.iter().map(|variant| Candidate {
+            name: Some(&variant.ident),
+            attrs: variant.attrs.iter().collect(),
+            types: variant.fields.iter().map(|field| &field.ty).collect()
+        }).collect(),
+        Definition::Type(_) => Vec::new()
+    };
+    select(definition, &candidates, selectors, nullable)
+}
+
+/// A candidate is selected when it matches any selector, ignoring negated ones, and none of the
+/// negated selectors. Without any selectors which are not negated, everything is selected unless
+/// a negated selector excludes it.
+fn select(definition: &Definition, candidates: &[Candidate], selectors: &[FieldSelector],
+          nullable: &[Path]) -> Result<Vec<bool>> {
+    validate_selectors(definition, candidates, selectors, nullable)?;
+    let (excluding, including): (Vec<&FieldSelector>, Vec<&FieldSelector>) = selectors.iter()
+        .partition(|selector| matches!(selector, FieldSelector::Not(_)));
+    Ok(candidates.iter().map(|candidate| {
+        (including.is_empty() || including.iter().any(|selector| matches(selector, candidate, nullable))) &&
+            excluding.iter().all(|selector| matches(selector, candidate, nullable))
+    }).collect())
+}
+
+fn matches(selector: &FieldSelector, candidate: &Candidate, nullable: &[Path]) -> bool {
+    match selector {
+        FieldSelector::Name(ident) => candidate.name == Some(ident),
+        FieldSelector::Glob(pattern, _) => candidate.name
+            .is_some_and(|name| glob_matches(pattern, &name.unraw().to_string())),
+        FieldSelector::Type(pattern) => candidate.types.iter().any(|ty| {
+            type_matches(pattern, ty) || nullable_argument(ty, nullable).is_some_and(|ty| type_matches(pattern, ty))
+        }),
+        FieldSelector::Attribute(path) => candidate.attrs.iter()
+            .any(|attr| attr.path().to_token_stream().to_string() == path.to_token_stream().to_string()),
+        FieldSelector::Not(selector) => !matches(selector, candidate, nullable)
+    }
+}
+
+/// Names must exist on `definition`, and every other selector has to match something, so typos do
+/// not silently select nothing.
+fn validate_selectors(definition: &Definition, candidates: &[Candidate], selectors: &[FieldSelector],
+                      nullable: &[Path]) -> Result<()> {
+    let mut names = Vec::new();
+    let mut errors = Vec::new();
+    for selector in selectors {
+        let mut selector = selector;
+        while let FieldSelector::Not(inner) = selector {
+            selector = inner;
+        }
+        match selector {
+            FieldSelector::Name(ident) => names.push(ident.clone()),
+            _ if candidates.iter().any(|candidate| matches(selector, candidate, nullable)) => {},
+            _ => errors.push(syn::Error::new(selector.span(), format!(
+                "`{}` does not match any {} of `{}`", selector,
+                if let Definition::Enum(_) = definition { "variant" } else { "field" }, definition.ident())))
+        }
+    }
+    validate_fields_exist(definition, &names)?;
+    combine_errors(errors)
+}
+
+/// Whether `name` matches `pattern`, where `*` matches any sequence of characters.
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == name,
+        Some((prefix, rest)) => match name.strip_prefix(prefix) {
+            None => false,
+            Some(name) => (0..=name.len())
+                .filter(|index| name.is_char_boundary(*index))
+                .any(|index| glob_matches(rest, &name[index..]))
+        }
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::{ItemEnum, ItemStruct, parse_quote};
+
+    #[test]
+    fn test_select_fields_by_type_param() {
+        let input: ItemStruct = parse_quote! {
+            struct Page<'a, T> {
+               item: T,
+               borrowed: &'a T,
+               maybe: Option<T>,
+               items: Vec<T>
+            }
+        };
+        let selectors = vec![parse_quote!(type: T)];
+
+        let actual = select_fields(&input.into(), &selectors, &[]).unwrap();
+        assert_eq!(actual, vec![true, false, true, false]);
+    }
+
+    #[test]
+    fn test_select_fields_without_selectors() {
+        let input: ItemStruct = parse_quote! {
+            struct Page<T> {
+               item: T,
+               count: usize
+            }
+        };
+
+        let actual = select_fields(&input.into(), &[], &[]).unwrap();
+        assert_eq!(actual, vec![true, true]);
+    }
+
+    #[test]
+    fn test_select_fields_by_name() {
+        let input: ItemStruct = parse_quote! {
+            struct User {
+               id: u64,
+               email: String,
+               phone: String
+            }
+        };
+        let selectors = vec![parse_quote!(phone), parse_quote!(email)];
+
+        let actual = select_fields(&input.into(), &selectors, &[]).unwrap();
+        assert_eq!(actual, vec![false, true, true]);
+    }
+
+    #[test]
+    fn test_select_fields_by_variant_name() {
+        let input: ItemEnum = parse_quote! {
+            enum State {
+               Loading,
+               Loaded { items: Vec<String>, total: u64 },
+               Failed(String)
+            }
+        };
+        let selectors = vec![parse_quote!(Loaded)];
+
+        let actual = select_fields(&input.into(), &selectors, &[]).unwrap();
+        assert_eq!(actual, vec![true, true, false]);
+    }
+
+    #[test]
+    fn test_select_fields_unknown_name() {
+        let input: ItemStruct = parse_quote! {
+            struct User {
+               id: u64
+            }
+        };
+        let selectors = vec![parse_quote!(email)];
+
+        let actual = select_fields(&input.into(), &selectors, &[]);
+        assert_eq!(actual.err().unwrap().to_string(), "Field `email` does not exist on type `User`");
+    }
+
+    #[test]
+    fn test_select_fields_undeclared_type_param() {
+        let input: ItemStruct = parse_quote! {
+            struct Page<T> {
+               item: T
+            }
+        };
+        let selectors = vec![parse_quote!(type: U)];
+
+        let actual = select_fields(&input.into(), &selectors, &[]);
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn test_select_fields_by_glob() {
+        let input: ItemStruct = parse_quote! {
+            struct User {
+               id: u64,
+               addr_line1: String,
+               addr_city: String,
+               created_at: u64,
+               r#type: String
+            }
+        };
+
+        let prefix = select_fields(&input.clone().into(), &[parse_quote!(addr_*)], &[]).unwrap();
+        let suffix = select_fields(&input.clone().into(), &[parse_quote!(*_at)], &[]).unwrap();
+        let raw = select_fields(&input.into(), &[parse_quote!(typ*)], &[]).unwrap();
+        assert_eq!(prefix, vec![false, true, true, false, false]);
+        assert_eq!(suffix, vec![false, false, false, true, false]);
+        assert_eq!(raw, vec![false, false, false, false, true]);
+    }
+
+    #[test]
+    fn test_select_fields_by_type_nullable() {
+        let input: ItemStruct = parse_quote! {
+            struct User {
+               id: u64,
+               nickname: Maybe<String>,
+               email: Option<String>,
+               name: String
+            }
+        };
+
+        let selectors = [parse_quote!(type: String)];
+        let custom = select_fields(&input.clone().into(), &selectors, &[parse_quote!(Maybe)]).unwrap();
+        let default = select_fields(&input.into(), &selectors, &[]).unwrap();
+        assert_eq!(custom, vec![false, true, true, true]);
+        assert_eq!(default, vec![false, false, true, true]);
+    }
+
+    #[test]
+    fn test_select_fields_by_type_pattern() {
+        let input: ItemStruct = parse_quote! {
+            struct User<'a> {
+               id: u64,
+               email: Option<String>,
+               tags: Option<Vec<&'a str>>,
+               name: &'a str
+            }
+        };
+
+        let optional = select_fields(&input.clone().into(), &[parse_quote!(type: Option<_>)], &[]).unwrap();
+        let nested = select_fields(&input.clone().into(), &[parse_quote!(type: Vec<&str>)], &[]).unwrap();
+        let borrowed = select_fields(&input.into(), &[parse_quote!(type: &'_ _)], &[]).unwrap();
+        assert_eq!(optional, vec![false, true, true, false]);
+        assert_eq!(nested, vec![false, false, true, false]);
+        assert_eq!(borrowed, vec![false, false, false, true]);
+    }
+
+    #[test]
+    fn test_select_fields_by_attribute() {
+        let input: ItemStruct = parse_quote! {
+            struct User {
+               id: u64,
+               #[pii]
+               email: String,
+               #[serde(rename = "tel")]
+               #[pii]
+               phone: String
+            }
+        };
+
+        let actual = select_fields(&input.into(), &[parse_quote!(#[pii])], &[]).unwrap();
+        assert_eq!(actual, vec![false, true, true]);
+    }
+
+    #[test]
+    fn test_select_fields_negated() {
+        let input: ItemStruct = parse_quote! {
+            struct User {
+               id: u64,
+               addr_line1: String,
+               addr_city: String,
+               name: String
+            }
+        };
+
+        let except = select_fields(&input.clone().into(), &[parse_quote!(!id)], &[]).unwrap();
+        let combined = select_fields(&input.into(), &[parse_quote!(addr_*), parse_quote!(!addr_city)], &[]).unwrap();
+        assert_eq!(except, vec![false, true, true, true]);
+        assert_eq!(combined, vec![false, true, false, false]);
+    }
+
+    #[test]
+    fn test_select_fields_unmatched_selector() {
+        let input: ItemStruct = parse_quote! {
+            struct User {
+               id: u64
+            }
+        };
+
+        let actual = select_fields(&input.into(), &[parse_quote!(!addr_*)], &[]);
+        assert_eq!(actual.err().unwrap().to_string(), "`addr_*` does not match any field of `User`");
+    }
+
+    #[test]
+    fn test_select_members_enum() {
+        let input: ItemEnum = parse_quote! {
+            enum Event<T> {
+               Created { id: u64, payload: T },
+               #[deprecated]
+               Renamed(String),
+               Deleted(u64)
+            }
+        };
+
+        let by_type = select_members(&input.clone().into(), &[parse_quote!(type: T)], &[]).unwrap();
+        let by_attribute = select_members(&input.into(), &[parse_quote!(!#[deprecated])], &[]).unwrap();
+        assert_eq!(by_type, vec![true, false, false]);
+        assert_eq!(by_attribute, vec![true, false, true]);
+    }
+}
@@ -0,0 +1,113 @@
+use syn::{Field, Path, Result, Type};
+use crate::parser::composite_struct::field_selector::FieldSelector;
+use crate::processor::composite_struct::composite_operations::utility_operations::helpers::promote_first_generic_argument;
+use crate::processor::composite_struct::composite_operations::utility_operations::selectors::select_fields;
+use crate::parser::definition::Definition;
+
+/// Unwraps every field of `definition` which is picked by `selectors` and wrapped in `wrapper`,
+/// e.g. `Box<T>` becomes `T`. Fields of any other type are kept as they are. A field type matches
+/// when its path ends with the path of `wrapper`, so `Box` also matches `std::boxed::Box<T>`, while
+/// `std::boxed::Box` doesn't match `Box<T>`.
+pub fn process_unwrap(definition: &Definition, wrapper: &Path, selectors: &[FieldSelector],
+                      nullable: &[Path]) -> Result<Definition> {
+    let selected = select_fields(definition, selectors, nullable)?;
+    let mut new_definition = definition.clone();
+    new_definition.append_to_ident("Unwrap");
+    new_definition.fields_mut().zip(selected)
+        .filter(|(field, selected)| *selected && is_wrapped_in(field, wrapper))
+        .for_each(|(field, _)| {
+            if let Some(unwrapped) = promote_first_generic_argument(field.clone()) {
+                *field = unwrapped;
+            }
+        });
+    Ok(new_definition)
+}
+
+fn is_wrapped_in(field: &Field, wrapper: &Path) -> bool {
+    let Type::Path(type_path) = &field.ty else { return false };
+    let path = &type_path.path;
+    if type_path.qself.is_some() || path.segments.len() < wrapper.segments.len() ||
+        (wrapper.leading_colon.is_some() && (path.leading_colon.is_none() || path.segments.len() != wrapper.segments.len())) {
+        return false
+    }
+    path.segments.iter().rev().zip(wrapper.segments.iter().rev())
+        .all(|(segment, wrapper)| segment.ident == wrapper.ident)
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use quote::ToTokens;
+    use super::*;
+    use syn::{ItemStruct, parse_quote};
+
+    #[test]
+    fn test_process_unwrap() {
+        let input: ItemStruct = parse_quote! {
+            struct Boxed<'a> {
+               config: Box<Config>,
+               name: std::boxed::Box<&'a str>,
+               rows: Vec<Box<u8>>
+            }
+        };
+
+        let expected: ItemStruct = parse_quote! {
+            struct BoxedUnwrap<'a> {
+               config: Config,
+               name: &'a str,
+               rows: Vec<Box<u8>>
+            }
+        };
+
+        let actual = process_unwrap(&input.into(), &parse_quote!(Box), &[], &[]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_unwrap_selected_fields() {
+        let input: ItemStruct = parse_quote! {
+            struct Shared {
+               config: Arc<Config>,
+               cache: Arc<Cache>
+            }
+        };
+
+        let expected: ItemStruct = parse_quote! {
+            struct SharedUnwrap {
+               config: Config,
+               cache: Arc<Cache>
+            }
+        };
+
+        let actual = process_unwrap(&input.into(), &parse_quote!(Arc), &[parse_quote!(!cache)], &[]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_unwrap_path() {
+        let input: ItemStruct = parse_quote! {
+            struct Shared {
+               config: std::boxed::Box<Config>,
+               cache: Box<Cache>,
+               other: other::Box<Other>
+            }
+        };
+
+        let expected: ItemStruct = parse_quote! {
+            struct SharedUnwrap {
+               config: Config,
+               cache: Box<Cache>,
+               other: other::Box<Other>
+            }
+        };
+
+        let actual = process_unwrap(&input.into(), &parse_quote!(std::boxed::Box), &[], &[]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+}
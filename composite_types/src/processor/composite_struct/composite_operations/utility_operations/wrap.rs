@@ -0,0 +1,93 @@
+use syn::{Path, Result};
+use crate::parser::composite_struct::field_selector::FieldSelector;
+use crate::processor::composite_struct::composite_operations::utility_operations::helpers::wrap_field_as_generic_arg;
+use crate::processor::composite_struct::composite_operations::utility_operations::selectors::select_fields;
+use crate::parser::definition::Definition;
+
+/// Wraps every field of `definition` which is picked by `selectors` in `wrapper`, e.g. `T` becomes
+/// `Arc<T>`. Unlike [process_optional](super::optional::process_optional), fields are wrapped even
+/// if they are already wrapped, so `Wrap(Rows, Vec)` turns `Vec<u8>` into `Vec<Vec<u8>>`.
+pub fn process_wrap(definition: &Definition, wrapper: &Path, selectors: &[FieldSelector],
+                    nullable: &[Path]) -> Result<Definition> {
+    let selected = select_fields(definition, selectors, nullable)?;
+    let mut new_definition = definition.clone();
+    new_definition.append_to_ident("Wrap");
+    new_definition.fields_mut().zip(selected)
+        .filter(|(_, selected)| *selected)
+        .for_each(|(field, _)| {
+            if let Some(wrapped) = wrap_field_as_generic_arg(field.clone(), wrapper.clone()) {
+                *field = wrapped;
+            }
+        });
+    Ok(new_definition)
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use quote::ToTokens;
+    use super::*;
+    use syn::{ItemEnum, ItemStruct, parse_quote};
+
+    #[test]
+    fn test_process_wrap() {
+        let input: ItemStruct = parse_quote! {
+            pub struct State<'a> {
+               pub config: Config,
+               name: &'a str,
+               rows: Vec<u8>
+            }
+        };
+
+        let expected: ItemStruct = parse_quote! {
+            pub struct StateWrap<'a> {
+               pub config: Arc<Config>,
+               name: Arc<&'a str>,
+               rows: Arc<Vec<u8> >
+            }
+        };
+
+        let actual = process_wrap(&input.into(), &parse_quote!(Arc), &[], &[]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_wrap_selected_fields() {
+        let input: ItemStruct = parse_quote! {
+            struct Row(u64, String);
+        };
+
+        let expected: ItemStruct = parse_quote! {
+            struct RowWrap(u64, Vec<String>);
+        };
+
+        let actual = process_wrap(&input.into(), &parse_quote!(Vec), &[parse_quote!(type: String)], &[]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_process_wrap_enum() {
+        let input: ItemEnum = parse_quote! {
+            enum Node {
+               Leaf(u64),
+               Branch { left: Node, right: Node }
+            }
+        };
+
+        let expected: ItemEnum = parse_quote! {
+            enum NodeWrap {
+               Leaf(u64),
+               Branch { left: Box<Node>, right: Box<Node> }
+            }
+        };
+
+        let actual = process_wrap(&input.into(), &parse_quote!(Box), &[parse_quote!(Branch)], &[]).unwrap();
+        assert_eq!(
+            actual.to_token_stream().to_string(),
+            expected.to_token_stream().to_string());
+    }
+}
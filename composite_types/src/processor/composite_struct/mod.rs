@@ -1,19 +1,49 @@
 use proc_macro2::Ident;
-use syn::{ItemStruct, parse_quote};
+use syn::Visibility;
+use crate::parser::definition::Definition;
 use crate::parser::composite_struct::CompositeStruct;
+use crate::parser::composite_struct::type_expression::TypeExpression;
 use crate::processor::composite_struct::composite_operations::process_composite_operation;
+use crate::processor::composite_struct::composite_operations::type_alias::{generate_copy_conversions, process_transparent_alias};
+use crate::processor::errors::{generate_from_copy_error, generate_internal_from_error, generate_kind_mismatch_error};
 use crate::processor::state::State;
 use syn::Result;
 
 pub mod composite_operations;
 
-pub fn process_composite_struct(mut state: &mut State, assignment: &Ident,
-                                comp: &CompositeStruct) -> Result<ItemStruct> {
-    let mut processed_structure = process_composite_operation(&mut state,
-                                                              assignment,
-                                                              &comp.composite_operation)?;
-    if comp.pub_token.is_some() {
-        processed_structure.vis = parse_quote! { pub };
+pub fn process_composite_struct(state: &mut State, assignment: &Ident,
+                                comp: &CompositeStruct) -> Result<Definition> {
+    let target = comp.composite_operation.alias_target();
+    let mut processed_structure = match (comp.kind(), &target) {
+        // `type Savings = BankAccount;` declares a Rust type alias, while `struct` declares a copy
+        ("type", Some(target)) => process_transparent_alias(state, assignment, target)?,
+        _ => process_composite_operation(state, assignment, &comp.composite_operation)?
+    };
+    if processed_structure.kind() != comp.kind() {
+        return Err(generate_kind_mismatch_error(&comp.name, comp.kind(), processed_structure.kind()))
+    }
+    // The definition is declared by the composition, so its spans point back to it
+    processed_structure.set_kind_span(comp.kind_span());
+    if let Some(pub_token) = comp.pub_token {
+        processed_structure.set_vis(Visibility::Public(pub_token));
+    }
+    processed_structure.attrs_mut().extend(comp.attrs.iter().cloned());
+    if let Some(span) = comp.settings.from {
+        let Some(target) = target.filter(|_| comp.kind() != "type") else {
+            return Err(generate_from_copy_error(span, &comp.name))
+        };
+        // The conversions would be left out along with the internal type
+        let source = match &target {
+            TypeExpression::Path(path) => state.resolve(path).ok(),
+            TypeExpression::Ident(ident) | TypeExpression::Generic(ident, _) => Some(state.key(ident)),
+            TypeExpression::UtilityOp(_) => None
+        };
+        if source.is_some_and(|source| state.internal.contains(&source)) {
+            return Err(generate_internal_from_error(span, &target, &comp.name))
+        }
+        let mut copy = processed_structure.clone();
+        copy.set_ident(assignment.clone());
+        state.impls.insert(state.key(assignment), generate_copy_conversions(&target, &copy));
     }
     Ok(processed_structure)
-}
\ No newline at end of file
+}
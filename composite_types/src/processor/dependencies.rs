@@ -0,0 +1,269 @@
+use std::collections::BTreeMap;
+use proc_macro2::Ident;
+use syn::visit::Visit;
+use syn::{Item, Result, TypePath};
+use crate::parser::composite_struct::CompositeOperation;
+use crate::parser::composite_struct::type_expression::TypeExpression;
+use crate::parser::composite_struct::utility_operations::UtilityOperation;
+use crate::parser::type_input::InputType;
+use crate::processor::state::State;
+
+/// A declaration which is processed into a definition, along with the module it is declared in
+/// and the keys of the types it refers to.
+pub struct Node<'a> {
+    pub key: String,
+    pub name: Ident,
+    pub scope: Vec<Ident>,
+    pub item: &'a InputType,
+    /// Types the declaration is composed from, which have to be processed before it.
+    dependencies: Vec<String>,
+    /// Types the declaration transforms along with the types their fields contain, e.g. the
+    /// operand of `DeepOptional`.
+    deep: Vec<String>,
+    /// Types named in the fields of a definition.
+    contained: Vec<String>,
+}
+
+impl<'a> Node<'a> {
+    /// Declaration `item` named `name` in the module being processed.
+    pub fn new(state: &State, name: &Ident, item: &'a InputType) -> Self {
+        Node {
+            key: state.key(name),
+            name: name.clone(),
+            scope: state.scope.clone(),
+            item,
+            dependencies: Vec::new(),
+            deep: Vec::new(),
+            contained: Vec::new(),
+        }
+    }
+
+    /// Collects the types the declaration refers to, from the module it is declared in. Types which
+    /// aren't declared in the macro are skipped, and reported once the declaration is processed.
+    pub fn collect_references(&mut self, state: &State) {
+        let mut references = References { state, dependencies: Vec::new(), deep: Vec::new() };
+        match self.item {
+            InputType::CompositeStruct(comp) => references.operation(&comp.composite_operation),
+            InputType::Definition(definition, _) => {
+                let mut fields = Fields { state, keys: &mut self.contained };
+                definition.fields().for_each(|field| fields.visit_type(&field.ty));
+            },
+            InputType::Item(Item::Type(alias)) => {
+                if let Some(target) = TypeExpression::from_alias(alias) {
+                    references.expression(&target);
+                }
+            },
+            InputType::Module(..) | InputType::Item(_) => {}
+        }
+        self.dependencies = references.dependencies;
+        self.deep = references.deep;
+    }
+}
+
+/// Orders the declarations so that each one comes after the declarations it is composed from,
+/// and otherwise keeps the order they are declared in. Declarations which are composed from
+/// themselves, directly or through other declarations, can't be ordered and are reported along
+/// with the cycle.
+pub fn sort_declarations<'a, 'b>(nodes: &'b [Node<'a>]) -> Result<Vec<&'b Node<'a>>> {
+    let indices = nodes.iter().enumerate()
+        .map(|(index, node)| (node.key.as_str(), index))
+        .collect::<BTreeMap<&str, usize>>();
+    let mut sorter = Sorter {
+        nodes,
+        indices,
+        visited: vec![false; nodes.len()],
+        path: Vec::new(),
+        order: Vec::new(),
+    };
+    for index in 0..nodes.len() {
+        sorter.visit(index)?;
+    }
+    Ok(sorter.order.into_iter().map(|index| &nodes[index]).collect())
+}
+
+struct Sorter<'a, 'b> {
+    nodes: &'b [Node<'a>],
+    indices: BTreeMap<&'b str, usize>,
+    visited: Vec<bool>,
+    /// Declarations being visited, from the outermost
+    path: Vec<usize>,
+    order: Vec<usize>,
+}
+
+impl Sorter<'_, '_> {
+    fn visit(&mut self, index: usize) -> Result<()> {
+        if self.visited[index] {
+            return Ok(())
+        }
+        if let Some(start) = self.path.iter().position(|visiting| *visiting == index) {
+            let cycle = self.path[start..].iter().chain(std::iter::once(&index))
+                .map(|visiting| format!("`{}`", self.nodes[*visiting].key))
+                .collect::<Vec<String>>();
+            return Err(syn::Error::new(self.nodes[index].name.span(), format!(
+                "Cyclic composition: {}. A type can't be composed from itself", cycle.join(" -> "))))
+        }
+        self.path.push(index);
+        let node = &self.nodes[index];
+        for dependency in node.dependencies.iter() {
+            if let Some(dependency) = self.indices.get(dependency.as_str()) {
+                self.visit(*dependency)?;
+            }
+        }
+        // Contained types only have to be processed first if they can be, since a type may
+        // contain the type transforming it
+        for contained in self.contained(node) {
+            if !self.path.contains(&contained) {
+                self.visit(contained)?;
+            }
+        }
+        self.path.pop();
+        self.visited[index] = true;
+        self.order.push(index);
+        Ok(())
+    }
+
+    /// Declarations named in the fields of the definitions `node` transforms deeply, following
+    /// the fields of contained definitions in turn.
+    fn contained(&self, node: &Node) -> Vec<usize> {
+        let mut pending = node.deep.clone();
+        let mut contained: Vec<usize> = Vec::new();
+        while let Some(key) = pending.pop() {
+            let Some(index) = self.indices.get(key.as_str()) else { continue };
+            if contained.contains(index) || *index == self.indices[node.key.as_str()] {
+                continue
+            }
+            contained.push(*index);
+            pending.extend(self.nodes[*index].contained.iter().cloned());
+        }
+        contained
+    }
+}
+
+/// Collects the keys of the types an operation is applied to.
+struct References<'a> {
+    state: &'a State,
+    dependencies: Vec<String>,
+    deep: Vec<String>,
+}
+
+impl References<'_> {
+    fn operation(&mut self, operation: &CompositeOperation) {
+        match operation {
+            CompositeOperation::TypeAlias(ident) | CompositeOperation::GenericAlias(ident, _) => {
+                self.dependencies.push(self.state.key(ident))
+            },
+            CompositeOperation::PathAlias(path) => {
+                self.dependencies.extend(self.state.path_key(path).ok())
+            },
+            CompositeOperation::UtilityOp(uo) => self.utility_operation(uo),
+            CompositeOperation::Union(members) => members.iter().for_each(|member| self.expression(member))
+        }
+    }
+
+    fn utility_operation(&mut self, operation: &UtilityOperation) {
+        match operation {
+            UtilityOperation::DeepOptional(expression) | UtilityOperation::DeepRequired(expression) => {
+                let start = self.dependencies.len();
+                self.expression(expression);
+                self.deep.extend(self.dependencies[start..].iter().cloned());
+            },
+            UtilityOperation::Required(expression, _) | UtilityOperation::Optional(expression, _) |
+            UtilityOperation::Pick(expression, _) | UtilityOperation::Omit(expression, _) |
+            UtilityOperation::KeyOf(expression) | UtilityOperation::FieldType(expression, _) |
+            UtilityOperation::Rename(expression, _) | UtilityOperation::Wrap(expression, _, _) |
+            UtilityOperation::Unwrap(expression, _, _) | UtilityOperation::Map(expression, _) => {
+                self.expression(expression)
+            },
+            UtilityOperation::Merge(expressions, _) => expressions.iter().for_each(|expression| self.expression(expression))
+        }
+    }
+
+    fn expression(&mut self, expression: &TypeExpression) {
+        match expression {
+            TypeExpression::Ident(ident) | TypeExpression::Generic(ident, _) => {
+                self.dependencies.push(self.state.key(ident))
+            },
+            TypeExpression::Path(path) => self.dependencies.extend(self.state.path_key(path).ok()),
+            TypeExpression::UtilityOp(uo) => self.utility_operation(uo)
+        }
+    }
+}
+
+/// Collects the keys of the types named by a single identifier in field types, like the types
+/// `DeepOptional` transforms.
+struct Fields<'a> {
+    state: &'a State,
+    keys: &'a mut Vec<String>,
+}
+
+impl<'ast> Visit<'ast> for Fields<'_> {
+    fn visit_type_path(&mut self, type_path: &'ast TypePath) {
+        if let Some(ident) = type_path.path.get_ident() {
+            self.keys.push(self.state.key(ident));
+        }
+        syn::visit::visit_type_path(self, type_path);
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    fn sort(items: &[InputType]) -> Result<Vec<String>> {
+        let state = State::new();
+        let nodes = items.iter().map(|item| {
+            let name = match item {
+                InputType::CompositeStruct(comp) => &comp.name,
+                InputType::Definition(definition, _) => definition.ident(),
+                _ => unreachable!()
+            };
+            let mut node = Node::new(&state, name, item);
+            node.collect_references(&state);
+            node
+        }).collect::<Vec<Node>>();
+        Ok(sort_declarations(&nodes)?.into_iter().map(|node| node.key.clone()).collect())
+    }
+
+    #[test]
+    fn test_sort_declarations() {
+        let items = vec![
+            InputType::CompositeStruct(parse_quote!(struct Patch = Optional(Account);)),
+            InputType::CompositeStruct(parse_quote!(struct Account = BankAccount & Pick(Owner, name);)),
+            InputType::Definition(parse_quote!(struct Owner { name: String }), Default::default()),
+            InputType::Definition(parse_quote!(struct BankAccount { balance: u64 }), Default::default()),
+        ];
+
+        let actual = sort(&items).unwrap();
+        assert_eq!(actual, vec!["BankAccount", "Owner", "Account", "Patch"]);
+    }
+
+    #[test]
+    fn test_sort_declarations_deep() {
+        let items = vec![
+            InputType::CompositeStruct(parse_quote!(struct UserPatch = DeepOptional(User);)),
+            InputType::Definition(parse_quote!(struct User { address: Vec<Address>, patch: Box<UserPatch> }), Default::default()),
+            InputType::CompositeStruct(parse_quote!(struct Address = Pick(Location, city);)),
+            InputType::Definition(parse_quote!(struct Location { city: String }), Default::default()),
+        ];
+
+        let actual = sort(&items).unwrap();
+        assert_eq!(actual, vec!["User", "Location", "Address", "UserPatch"]);
+    }
+
+    #[test]
+    fn test_sort_declarations_cycle() {
+        let items = vec![
+            InputType::CompositeStruct(parse_quote!(struct A = Optional(B);)),
+            InputType::CompositeStruct(parse_quote!(struct B = Pick(C, id);)),
+            InputType::CompositeStruct(parse_quote!(struct C = A & D;)),
+            InputType::Definition(parse_quote!(struct D { id: u64 }), Default::default()),
+        ];
+
+        let actual = sort(&items);
+        assert_eq!(
+            actual.err().unwrap().to_string(),
+            "Cyclic composition: `A` -> `B` -> `C` -> `A`. A type can't be composed from itself");
+    }
+}
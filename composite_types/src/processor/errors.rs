@@ -1,10 +1,88 @@
-use proc_macro2::Ident;
-use syn::{ItemStruct, Result};
+use proc_macro2::{Ident, Span};
+use syn::{Path, Result};
+use syn::spanned::Spanned;
+use crate::parser::composite_struct::type_expression::TypeExpression;
+use crate::parser::definition::Definition;
 
-pub fn generate_not_found_error(ident: &Ident) -> Result<ItemStruct> {
+pub fn generate_not_found_error<T>(ident: &Ident) -> Result<T> {
     let ident_string = ident.to_string();
     let error_message = format!(r#"Type `{0}` does not exist in the scope of the macro.
-        Please ensure that the type is defined before it is used. For example:
+        Please ensure that the type is defined in the macro. For example:
          `struct {0} = ...` or `struct {0} {{ ... }}` within the scope of compose_type!"#, ident_string);
-    return Err(syn::Error::new(ident.span(), error_message.as_str()))
-}
\ No newline at end of file
+    Err(syn::Error::new(ident.span(), error_message.as_str()))
+}
+
+/// Error for a path whose module or type, named by `key` relative to the root of the macro, is not
+/// declared in the macro.
+pub fn generate_path_not_found_error(path: &Path, kind: &str, key: &str) -> syn::Error {
+    syn::Error::new(path.span(), format!("{} `{}` does not exist in the scope of the macro", kind, key))
+}
+
+/// Error for `#[compose(from)]` on a declaration other than a copy, which has no type to convert
+/// from and to.
+pub fn generate_from_copy_error(span: Span, ident: &Ident) -> syn::Error {
+    syn::Error::new(span, format!(
+        "`#[compose(from)]` only applies to copies like `struct {} = BankAccount;`", ident))
+}
+
+/// Error for `#[compose(from)]` on a copy of an internal type, which isn't emitted and so can't be
+/// converted from or to.
+pub fn generate_internal_from_error(span: Span, internal: &TypeExpression, ident: &Ident) -> syn::Error {
+    syn::Error::new(span, format!(
+        "`{}` is declared with `#[compose(internal)]` and isn't emitted, so `{}` can't be converted from and to it",
+        internal, ident))
+}
+
+/// Combines every error into a single error so that all of them are reported at once.
+pub fn combine_errors(errors: impl IntoIterator<Item = syn::Error>) -> Result<()> {
+    let mut errors = errors.into_iter();
+    if let Some(mut error) = errors.next() {
+        errors.for_each(|e| error.combine(e));
+        return Err(error)
+    }
+    Ok(())
+}
+
+pub fn generate_field_not_found_error(definition: &Definition, field: &Ident) -> syn::Error {
+    let error_message = format!("Field `{}` does not exist on type `{}`", field, definition.ident());
+    syn::Error::new(field.span(), error_message.as_str())
+}
+
+pub fn generate_variant_not_found_error(definition: &Definition, variant: &Ident) -> syn::Error {
+    let error_message = format!("Variant `{}` does not exist on enum `{}`", variant, definition.ident());
+    syn::Error::new(variant.span(), error_message.as_str())
+}
+
+pub fn generate_duplicate_field_error(field: &Ident, first: &TypeExpression, second: &TypeExpression) -> syn::Error {
+    let error_message = format!(r#"Field `{0}` is defined by both `{1}` and `{2}`.
+        Use `Merge({1}, {2}, conflict = first)` or `Merge({1}, {2}, conflict = last)` to choose which field is kept."#,
+                                field, first, second);
+    syn::Error::new(second.span(), error_message.as_str())
+}
+
+pub fn generate_duplicate_variant_error(variant: &Ident, first: &TypeExpression, second: &TypeExpression) -> syn::Error {
+    let error_message = format!(r#"Variant `{0}` is defined by both `{1}` and `{2}`.
+        Use `Merge({1}, {2}, conflict = first)` or `Merge({1}, {2}, conflict = last)` to choose which variant is kept."#,
+                                variant, first, second);
+    syn::Error::new(second.span(), error_message.as_str())
+}
+
+pub fn generate_kind_mismatch_error(name: &Ident, declared: &str, produced: &str) -> syn::Error {
+    let error_message = format!("`{0}` is declared as {1} {2}, but its composition produces {3} {4}. Declare it as `{4} {0} = ...` instead.",
+                                name, article(declared), declared, article(produced), produced);
+    syn::Error::new(name.span(), error_message.as_str())
+}
+
+/// Indefinite article for a kind of definition, e.g. `an` for `enum`.
+pub fn article(kind: &str) -> &'static str {
+    match kind {
+        "enum" => "an",
+        _ => "a"
+    }
+}
+
+pub fn generate_type_alias_operand_error(expression: &TypeExpression, definition: &Definition) -> syn::Error {
+    let error_message = format!("`{}` is the type alias `{}`, and type aliases can not be used as operands of an operation",
+                                expression, definition.ident());
+    syn::Error::new(expression.span(), error_message.as_str())
+}
@@ -0,0 +1,138 @@
+use proc_macro2::{Ident, Span};
+use syn::visit::Visit;
+use syn::{Item, ItemImpl, Result, Type, TypePath};
+use crate::parser::composite_struct::type_expression::format_path;
+use crate::parser::definition::Definition;
+use crate::processor::errors::combine_errors;
+use crate::processor::state::{parent, Declaration, State};
+
+/// Checks that the definitions which are expanded don't refer to internal types in their fields or
+/// as the type they alias, since internal types aren't expanded. Implementations between an
+/// expanded type and an internal type, e.g. the conversions generated by `Rename`, are dropped
+/// instead.
+pub fn validate_internal(state: &mut State) -> Result<()> {
+    if state.internal.is_empty() {
+        return Ok(())
+    }
+    let declarations = state.declarations.iter()
+        .filter_map(|declaration| match declaration {
+            Declaration::Definition(key) if !state.internal.contains(key) => {
+                let types = match &state.variables[key] {
+                    Definition::Type(alias) => vec![alias.ty.as_ref().clone()],
+                    definition => definition.fields().map(|field| field.ty.clone()).collect()
+                };
+                Some((key.clone(), types))
+            },
+            // Aliases of types declared in the macro, e.g. `type Account = BankAccount;`
+            Declaration::Item(module, item) => match item.as_ref() {
+                Item::Type(alias) => Some((child(module, &alias.ident), vec![alias.ty.as_ref().clone()]))
+                    .filter(|(key, _)| state.aliases.contains_key(key)),
+                _ => None
+            },
+            _ => None
+        })
+        .collect::<Vec<(String, Vec<Type>)>>();
+    let mut errors = Vec::new();
+    for (key, types) in declarations {
+        // Types are named relative to the module the definition is declared in
+        state.scope = parent(&key).split("::")
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| Ident::new(segment, Span::call_site()))
+            .collect();
+        let mut references = References { state, internal: Vec::new() };
+        types.iter().for_each(|ty| references.visit_type(ty));
+        errors.extend(references.internal.into_iter().map(|(internal, span)| syn::Error::new(span, format!(
+            "`{}` is declared with `#[compose(internal)]` and isn't emitted, so `{}` can't refer to it",
+            internal, key))));
+        let implementations = state.impls.get(&key).map(|implementations| implementations.iter()
+            .filter(|implementation| !refers_to_internal(state, implementation))
+            .cloned()
+            .collect::<Vec<ItemImpl>>());
+        if let Some(implementations) = implementations {
+            state.impls.insert(key, implementations);
+        }
+    }
+    state.scope.clear();
+    combine_errors(errors)
+}
+
+/// Key of the item named `name` in the module `module`.
+fn child(module: &str, name: &Ident) -> String {
+    match module {
+        "" => name.to_string(),
+        module => format!("{}::{}", module, name)
+    }
+}
+
+fn refers_to_internal(state: &State, implementation: &ItemImpl) -> bool {
+    let mut references = References { state, internal: Vec::new() };
+    references.visit_type(&implementation.self_ty);
+    if let Some((_, path, _)) = &implementation.trait_ {
+        references.visit_path(path);
+    }
+    !references.internal.is_empty()
+}
+
+/// Collects the internal types named in the module being processed, with the spans they are
+/// named at.
+struct References<'a> {
+    state: &'a State,
+    internal: Vec<(String, Span)>,
+}
+
+impl<'ast> Visit<'ast> for References<'_> {
+    fn visit_type_path(&mut self, type_path: &'ast TypePath) {
+        let key = match type_path.path.get_ident() {
+            _ if type_path.qself.is_some() => None,
+            Some(ident) => Some(self.state.key(ident)),
+            None => self.state.path_key(&type_path.path).ok()
+        };
+        if key.is_some_and(|key| self.state.internal.contains(&key)) {
+            let name = format_path(&type_path.path);
+            self.internal.push((name, type_path.path.segments.last().unwrap().ident.span()));
+        }
+        syn::visit::visit_type_path(self, type_path);
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::ToTokens;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_validate_internal() {
+        let mut state = State::new();
+        state.insert("Base".to_string(), parse_quote!(struct Base { id: u64 }));
+        state.insert("Record".to_string(), parse_quote!(struct Record { record_id: u64 }));
+        state.insert("Patch".to_string(), parse_quote!(struct Patch { id: Option<u64> }));
+        state.internal.insert("Base".to_string());
+        state.impls.insert("Record".to_string(), vec![
+            parse_quote!(impl From<Base> for Record { fn from(value: Base) -> Self { todo!() } }),
+            parse_quote!(impl Default for Record { fn default() -> Self { todo!() } }),
+        ]);
+
+        validate_internal(&mut state).unwrap();
+        let actual = state.impls["Record"].iter()
+            .map(|implementation| implementation.self_ty.to_token_stream().to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(actual, vec!["Record"]);
+        assert_eq!(state.impls["Record"][0].trait_.as_ref().unwrap().1.to_token_stream().to_string(), "Default");
+    }
+
+    #[test]
+    fn test_validate_internal_field() {
+        let mut state = State::new();
+        state.insert("models::Base".to_string(), parse_quote!(struct Base { id: u64 }));
+        state.insert("User".to_string(), parse_quote!(struct User { base: Vec<models::Base> }));
+        state.modules.insert("models".to_string(), parse_quote!(mod models;));
+        state.internal.insert("models::Base".to_string());
+
+        let actual = validate_internal(&mut state);
+        assert_eq!(
+            actual.err().unwrap().to_string(),
+            "`models::Base` is declared with `#[compose(internal)]` and isn't emitted, so `User` can't refer to it");
+    }
+}
@@ -1,58 +1,141 @@
 mod errors;
 mod state;
 mod composite_struct;
+mod dependencies;
+mod internal;
 
-use std::collections::BTreeMap;
 use proc_macro2::Ident;
-use quote::ToTokens;
 use crate::parser::type_input::{InputType, TypeInput};
-use syn::{ItemStruct, Result};
+use syn::{Item, Result};
+use crate::parser::composite_struct::type_expression::TypeExpression;
+use crate::parser::definition::Definition;
+use crate::parser::settings::ItemSettings;
 
 use crate::processor::composite_struct::process_composite_struct;
-use crate::processor::state::State;
+use crate::processor::composite_struct::composite_operations::type_alias::register_alias;
+use crate::processor::dependencies::{Node, sort_declarations};
+use crate::processor::errors::generate_from_copy_error;
+use crate::processor::internal::validate_internal;
+use crate::processor::state::{Declaration, State};
 
-pub fn process_input<'a>(input: TypeInput) -> Result<State> {
-    let mut state = State {
-        variables: BTreeMap::new()
-    };
-    for item in input.items.iter() {
-        let assignment_ident = get_item_name(item);
-        validate_no_conflicts(&state, assignment_ident).unwrap();
-        // Run different processing logic for each operation
-        let new_structure: ItemStruct = match item {
-            InputType::CompositeStruct(comp) => {
-                process_composite_struct(&mut state, assignment_ident, comp)?
-            },
-            InputType::Definition(def) => def.to_owned()
-        };
-        apply_structure(&mut state, assignment_ident, new_structure);
+pub fn process_input(input: TypeInput) -> Result<State> {
+    let mut state = State::new();
+    state.settings = input.settings.clone();
+    let mut nodes = Vec::new();
+    declare_items(&mut state, &input.items, &mut nodes)?;
+    // Paths can only be resolved once every module is declared
+    for node in nodes.iter_mut() {
+        state.scope = node.scope.clone();
+        node.collect_references(&state);
     }
+    // Types may be used before they are declared, so declarations are processed after the
+    // declarations they are composed from
+    for node in sort_declarations(&nodes)? {
+        state.scope = node.scope.clone();
+        let generated = state.declarations.len();
+        process_declaration(&mut state, node)?;
+        // Types generated for the declaration are expanded right before it
+        let generated = state.declarations.split_off(generated);
+        let position = state.declarations.iter()
+            .position(|declaration| matches!(declaration, Declaration::Definition(key) if *key == node.key))
+            .unwrap_or(state.declarations.len());
+        state.declarations.splice(position..position, generated);
+    }
+    state.scope.clear();
+    validate_internal(&mut state)?;
     Ok(state)
 }
 
-fn apply_structure(state: &mut State, assignment: &Ident, mut structure: ItemStruct) {
-    structure.ident = assignment.clone();
-    state.variables.insert(assignment.to_string(), structure.clone());
+/// Adds every item to the state in the order they are declared, so they are expanded in that
+/// order, and collects the declarations which are processed into definitions. Items of a module
+/// are declared with the module as the scope, so their names and the paths they use are relative
+/// to it.
+fn declare_items<'a>(state: &mut State, items: &'a [InputType], nodes: &mut Vec<Node<'a>>) -> Result<()> {
+    for item in items.iter() {
+        match item {
+            InputType::CompositeStruct(comp) => declare(state, &comp.name, comp.settings, item, nodes)?,
+            InputType::Definition(def, settings) => declare(state, def.ident(), *settings, item, nodes)?,
+            InputType::Module(module, items) => {
+                let key = validate_no_conflicts(state, &module.ident)?;
+                state.insert_module(key, module.clone());
+                state.scope.push(module.ident.clone());
+                let result = declare_items(state, items, nodes);
+                state.scope.pop();
+                result?;
+            },
+            InputType::Item(declared) => {
+                state.declarations.push(Declaration::Item(state.module(), Box::new(declared.clone())));
+                // Aliases may name types declared in the macro, which are processed first
+                if let Item::Type(alias) = declared {
+                    if TypeExpression::from_alias(alias).is_some() {
+                        nodes.push(Node::new(state, &alias.ident, item));
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
 }
 
-fn get_item_name(item: &InputType) -> &Ident {
-    match item {
-        InputType::CompositeStruct(comp) => &comp.name,
-        InputType::Definition(def) => &def.ident
+fn declare<'a>(state: &mut State, name: &Ident, settings: ItemSettings, item: &'a InputType,
+               nodes: &mut Vec<Node<'a>>) -> Result<()> {
+    let key = validate_no_conflicts(state, name)?;
+    if settings.internal {
+        state.internal.insert(key.clone());
     }
+    state.declarations.push(Declaration::Definition(key));
+    nodes.push(Node::new(state, name, item));
+    Ok(())
 }
 
-fn validate_no_conflicts(state: &State, key: &Ident) -> Result<()> {
-    if state.variables.contains_key(key.to_string().as_str()) {
-        return Err(syn::Error::new(key.span(),"This identifier is already in use"))
+fn process_declaration(state: &mut State, node: &Node) -> Result<()> {
+    // Types generated by other declarations may have taken the name
+    if state.variables.contains_key(&node.key) {
+        return Err(syn::Error::new(node.name.span(), "This identifier is already in use"))
     }
+    // Run different processing logic for each operation
+    let new_structure: Definition = match node.item {
+        InputType::CompositeStruct(comp) => {
+            process_composite_struct(state, &node.name, comp)?
+        },
+        InputType::Definition(def, settings) => {
+            if let Some(span) = settings.from {
+                return Err(generate_from_copy_error(span, def.ident()))
+            }
+            def.to_owned()
+        },
+        // Aliases are emitted as written, but operations can use them like the type they name
+        InputType::Item(Item::Type(alias)) => {
+            if let Some(target) = TypeExpression::from_alias(alias) {
+                register_alias(state, &alias.ident, &target)?;
+            }
+            return Ok(())
+        },
+        // Modules and items are added to the state when they are declared
+        InputType::Module(..) | InputType::Item(_) => return Ok(())
+    };
+    apply_structure(state, &node.name, new_structure);
     Ok(())
 }
 
+fn apply_structure(state: &mut State, assignment: &Ident, mut structure: Definition) {
+    structure.set_ident(assignment.clone());
+    state.insert(state.key(assignment), structure.clone());
+}
+
+/// Key of the item named `key` in the module being declared, which has to be unique.
+fn validate_no_conflicts(state: &State, key: &Ident) -> Result<String> {
+    let key_string = state.key(key);
+    if state.is_declared(&key_string) || state.modules.contains_key(&key_string) {
+        return Err(syn::Error::new(key.span(),"This identifier is already in use"))
+    }
+    Ok(key_string)
+}
+
 // unit tests
 #[cfg(test)]
 mod tests {
-    use maplit::btreemap;
+    use maplit::{btreemap, btreeset};
     use super::*;
     use syn::parse_quote;
 
@@ -69,6 +152,13 @@ mod tests {
         let actual = process_input(input).unwrap();
 
         let expected = State {
+            aliases: btreemap! {},
+            impls: btreemap! {},
+            declarations: vec![],
+            internal: btreeset! {},
+            modules: btreemap! {},
+            scope: vec![],
+            settings: Default::default(),
             variables: btreemap! {
                 "MyStruct".to_string() => parse_quote! {
                     struct MyStruct {
@@ -1,35 +1,195 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use crate::parser::definition::Definition;
+use crate::parser::composite_struct::type_expression::format_path;
+use crate::parser::settings::Settings;
+use crate::processor::errors::generate_path_not_found_error;
+use proc_macro2::Ident;
 use quote::ToTokens;
-use syn::ItemStruct;
+use syn::{Item, ItemImpl, ItemMod, Path, Result};
+use syn::token::Brace;
+use syn::spanned::Spanned;
 use std::fmt::{Debug, Formatter};
 
 
+/// An entry of the expanded output. Definitions and modules are stored by key, so operations can
+/// look them up and replace them without moving them.
+pub enum Declaration {
+    /// Key of a definition, which is expanded with its implementations.
+    Definition(String),
+    /// Key of a module, which is expanded with the declarations inside of it.
+    Module(String),
+    /// Item passed through unchanged, e.g. a `use` declaration or an `impl` block, and the path of
+    /// the module it is declared in.
+    Item(String, Box<Item>),
+}
+
 /// Storage for the State of the processor.
 pub struct State {
-    /// Stores the state of exposed data structures and intermediate data structures.
-    pub variables: BTreeMap<String, ItemStruct>
+    /// Stores the state of exposed data structures and intermediate data structures, keyed by
+    /// their path within the macro, e.g. `models::User`.
+    pub variables: BTreeMap<String, Definition>,
+    /// Definitions named by type aliases like `type Savings = BankAccount;`, keyed by the alias,
+    /// which operations see in place of the alias.
+    pub aliases: BTreeMap<String, Definition>,
+    /// Implementations generated for exposed data structures, e.g. conversions, keyed by the name
+    /// of the data structure they belong to.
+    pub impls: BTreeMap<String, Vec<ItemImpl>>,
+    /// Everything which is expanded, in the order it was declared.
+    pub declarations: Vec<Declaration>,
+    /// Keys of definitions declared with `#[compose(internal)]`, which operations can use but
+    /// which aren't expanded.
+    pub internal: BTreeSet<String>,
+    /// Modules declared in the macro without their content, keyed by their path, e.g. `models`.
+    pub modules: BTreeMap<String, ItemMod>,
+    /// Path of the module whose items are being processed, empty at the root of the macro.
+    pub scope: Vec<Ident>,
+    /// Settings of the macro invocation.
+    pub settings: Settings
 }
 
 impl State {
     pub fn new() -> Self {
         State {
-            variables: BTreeMap::new()
+            variables: BTreeMap::new(),
+            aliases: BTreeMap::new(),
+            impls: BTreeMap::new(),
+            declarations: Vec::new(),
+            internal: BTreeSet::new(),
+            modules: BTreeMap::new(),
+            scope: Vec::new(),
+            settings: Settings::default()
+        }
+    }
+
+    /// Adds a definition under `key`. It is expanded where it was declared, or where it was first
+    /// added if it wasn't declared, so replacing it keeps its position.
+    pub fn insert(&mut self, key: String, definition: Definition) {
+        if !self.is_declared(&key) {
+            self.declarations.push(Declaration::Definition(key.clone()));
+        }
+        self.variables.insert(key, definition);
+    }
+
+    /// Definition operations see for the type with `key`, which is the definition it names for a
+    /// type alias.
+    pub fn lookup(&self, key: &str) -> Option<&Definition> {
+        self.aliases.get(key).or_else(|| self.variables.get(key))
+    }
+
+    /// Whether a definition is expanded under `key`, even if it hasn't been processed yet.
+    pub fn is_declared(&self, key: &str) -> bool {
+        self.declarations.iter().any(|declaration| matches!(declaration, Declaration::Definition(k) if k == key))
+    }
+
+    /// Adds a module under `key`, which is expanded where it was added.
+    pub fn insert_module(&mut self, key: String, module: ItemMod) {
+        if self.modules.insert(key.clone(), module).is_none() {
+            self.declarations.push(Declaration::Module(key));
+        }
+    }
+
+    /// Key of the item named `name` in the module being processed, e.g. `models::User`.
+    pub fn key(&self, name: &Ident) -> String {
+        self.scope.iter().chain(std::iter::once(name))
+            .map(|ident| ident.to_string())
+            .collect::<Vec<String>>()
+            .join("::")
+    }
+
+    /// Path of the module being processed, e.g. `models`, empty at the root of the macro.
+    pub fn module(&self) -> String {
+        self.scope.iter().map(|ident| ident.to_string()).collect::<Vec<String>>().join("::")
+    }
+
+    /// Key of the definition `path` refers to from the module being processed. Like in Rust,
+    /// paths start from the current module and may move up with `super`. Paths starting from
+    /// `crate` or `::` leave the macro, so they can't be resolved.
+    pub fn resolve(&self, path: &Path) -> Result<String> {
+        let key = self.path_key(path)?;
+        if self.lookup(&key).is_none() {
+            return Err(generate_path_not_found_error(path, "Type", &key))
         }
+        Ok(key)
+    }
+
+    /// Key `path` refers to from the module being processed, which only has to be declared for
+    /// the modules it passes through.
+    pub fn path_key(&self, path: &Path) -> Result<String> {
+        let (last, modules) = match path.segments.iter().collect::<Vec<_>>().split_last() {
+            Some((last, modules)) => (*last, modules.to_vec()),
+            None => return Err(syn::Error::new(path.span(), "Expected a path"))
+        };
+        if path.leading_colon.is_some() || modules.first().is_some_and(|segment| segment.ident == "crate") {
+            return Err(syn::Error::new(path.span(), format!(
+                "`{}` can't be resolved, since only paths relative to the macro are supported, e.g. `models::User`",
+                format_path(path))))
+        }
+        let mut module = self.scope.iter().map(|ident| ident.to_string()).collect::<Vec<String>>();
+        for segment in modules {
+            if !segment.arguments.is_none() {
+                return Err(syn::Error::new(segment.span(), format!(
+                    "Only the last segment of `{}` can have generic arguments", format_path(path))))
+            }
+            match segment.ident.to_string().as_str() {
+                "self" => {},
+                "super" => if module.pop().is_none() {
+                    return Err(syn::Error::new(segment.span(), format!(
+                        "`{}` refers to a module outside of the macro, which can't be resolved", format_path(path))))
+                },
+                name => {
+                    module.push(name.to_string());
+                    if !self.modules.contains_key(&module.join("::")) {
+                        return Err(generate_path_not_found_error(path, "Module", &module.join("::")))
+                    }
+                }
+            }
+        }
+        module.push(last.ident.to_string());
+        Ok(module.join("::"))
     }
 
     /// Expands the state into a TokenStream representing the macro expanded tokens.
     pub fn expand(&self) -> proc_macro2::TokenStream {
+        self.expand_module("")
+    }
+
+    /// Expands the declarations inside of `module` in the order they were declared, keeping the
+    /// spans they were declared with.
+    fn expand_module(&self, module: &str) -> proc_macro2::TokenStream {
         let mut output = proc_macro2::TokenStream::new();
-        for item in self.variables.iter() {
-            let (_, value) = item;
-            output.extend(value.to_token_stream());
+        for declaration in self.declarations.iter() {
+            match declaration {
+                Declaration::Definition(key) if parent(key) == module && !self.internal.contains(key) => {
+                    self.variables[key].to_tokens(&mut output);
+                    for implementation in self.impls.get(key).into_iter().flatten() {
+                        implementation.to_tokens(&mut output);
+                    }
+                },
+                Declaration::Module(key) if parent(key) == module => {
+                    let ItemMod { attrs, vis, mod_token, ident, content, .. } = &self.modules[key];
+                    output.extend(attrs.iter().map(ToTokens::to_token_stream));
+                    vis.to_tokens(&mut output);
+                    mod_token.to_tokens(&mut output);
+                    ident.to_tokens(&mut output);
+                    let brace = content.as_ref().map_or_else(Brace::default, |(brace, _)| *brace);
+                    brace.surround(&mut output, |tokens| tokens.extend(self.expand_module(key)));
+                },
+                Declaration::Item(item_module, item) if item_module == module => item.to_tokens(&mut output),
+                _ => {}
+            }
         }
         output
     }
 }
 
+/// Path of the module the item with `key` is declared in, empty at the root of the macro.
+pub fn parent(key: &str) -> &str {
+    key.rsplit_once("::").map_or("", |(module, _)| module)
+}
+
 // unit tests
 #[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod tests {
     use super::*;
     use syn::parse_quote;
@@ -37,12 +197,12 @@ mod tests {
     #[test]
     fn test_expand() {
         let mut state = State::new();
-        let structure: ItemStruct = parse_quote! {
+        let structure: Definition = parse_quote! {
             struct Test {
                 field: FieldType
             }
         };
-        state.variables.insert("Test".to_string(), structure);
+        state.insert("Test".to_string(), structure);
 
         let expected: proc_macro2::TokenStream = parse_quote! {
             struct Test {
@@ -57,26 +217,26 @@ mod tests {
     #[test]
     fn test_expand_multiple() {
         let mut state = State::new();
-        let structure: ItemStruct = parse_quote! {
+        let structure: Definition = parse_quote! {
             struct Test {
                 field: FieldType
             }
         };
-        state.variables.insert("Test".to_string(), structure);
+        state.insert("Test".to_string(), structure);
 
-        let structure: ItemStruct = parse_quote! {
+        let structure: Definition = parse_quote! {
             struct Test2 {
                 field: FieldType
             }
         };
-        state.variables.insert("Test2".to_string(), structure);
+        state.insert("Test2".to_string(), structure);
 
-        let structure: ItemStruct = parse_quote! {
+        let structure: Definition = parse_quote! {
             struct Test3 {
                 field: Test2
             }
         };
-        state.variables.insert("Test3".to_string(), structure);
+        state.insert("Test3".to_string(), structure);
 
         let expected: proc_macro2::TokenStream = parse_quote! {
             struct Test {
@@ -95,13 +255,111 @@ mod tests {
         let actual = state.expand();
         assert_eq!(actual.to_string(), expected.to_string());
     }
+
+    #[test]
+    fn test_expand_in_declaration_order() {
+        let mut state = State::new();
+        state.insert("Zebra".to_string(), parse_quote!(struct Zebra;));
+        state.insert("Apple".to_string(), parse_quote!(struct Apple;));
+        state.insert("Zebra".to_string(), parse_quote!(struct Zebra(u8);));
+
+        let expected: proc_macro2::TokenStream = parse_quote! {
+            struct Zebra(u8);
+            struct Apple;
+        };
+
+        let actual = state.expand();
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_expand_modules() {
+        let mut state = State::new();
+        state.insert_module("models".to_string(), parse_quote!(pub mod models;));
+        state.insert("models::User".to_string(), parse_quote!(pub struct User;));
+        state.insert_module("models::admin".to_string(), parse_quote!(mod admin;));
+        state.insert("models::admin::Admin".to_string(), parse_quote!(struct Admin;));
+        state.insert("Patch".to_string(), parse_quote!(struct Patch;));
+
+        let expected: proc_macro2::TokenStream = parse_quote! {
+            pub mod models {
+                pub struct User;
+                mod admin {
+                    struct Admin;
+                }
+            }
+            struct Patch;
+        };
+
+        let actual = state.expand();
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_expand_items() {
+        let mut state = State::new();
+        state.declarations.push(Declaration::Item("".to_string(), Box::new(parse_quote!(use std::fmt::Debug;))));
+        state.insert("Patch".to_string(), parse_quote!(struct Patch;));
+        state.insert_module("models".to_string(), parse_quote!(mod models;));
+        state.declarations.push(Declaration::Item("models".to_string(), Box::new(parse_quote!(use super::*;))));
+        state.insert("models::User".to_string(), parse_quote!(struct User;));
+        state.declarations.push(Declaration::Item("models".to_string(), Box::new(parse_quote!(impl User {}))));
+
+        let expected: proc_macro2::TokenStream = parse_quote! {
+            use std::fmt::Debug;
+            struct Patch;
+            mod models {
+                use super::*;
+                struct User;
+                impl User {}
+            }
+        };
+
+        let actual = state.expand();
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_expand_internal() {
+        let mut state = State::new();
+        state.insert("Base".to_string(), parse_quote!(struct Base;));
+        state.insert("Patch".to_string(), parse_quote!(struct Patch;));
+        state.internal.insert("Base".to_string());
+
+        let actual = state.expand();
+        assert_eq!(actual.to_string(), "struct Patch ;");
+    }
+
+    #[test]
+    fn test_resolve() {
+        let mut state = State::new();
+        state.modules.insert("models".to_string(), parse_quote!(mod models;));
+        state.modules.insert("models::admin".to_string(), parse_quote!(mod admin;));
+        state.variables.insert("models::User".to_string(), parse_quote!(struct User;));
+        state.scope = vec![parse_quote!(models), parse_quote!(admin)];
+
+        assert_eq!(state.resolve(&parse_quote!(super::User)).unwrap(), "models::User");
+        assert_eq!(state.resolve(&parse_quote!(self::super::super::models::User)).unwrap(), "models::User");
+        assert_eq!(
+            state.resolve(&parse_quote!(super::Admin)).err().unwrap().to_string(),
+            "Type `models::Admin` does not exist in the scope of the macro");
+        assert_eq!(
+            state.resolve(&parse_quote!(super::super::super::User)).err().unwrap().to_string(),
+            "`super::super::super::User` refers to a module outside of the macro, which can't be resolved");
+        assert_eq!(
+            state.resolve(&parse_quote!(crate::models::User)).err().unwrap().to_string(),
+            "`crate::models::User` can't be resolved, since only paths relative to the macro are supported, e.g. `models::User`");
+    }
 }
 
 impl Debug for State {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        for item in self.variables.iter() {
-            let (key, value) = item;
-            writeln!(f, "{}: {}", key, value.to_token_stream().to_string())?;
+        for declaration in self.declarations.iter() {
+            let Declaration::Definition(key) = declaration else { continue };
+            writeln!(f, "{}: {}", key, self.variables[key].to_token_stream())?;
+            for implementation in self.impls.get(key).into_iter().flatten() {
+                writeln!(f, "{}: {}", key, implementation.to_token_stream())?;
+            }
         }
         Ok(())
     }
@@ -109,7 +367,10 @@ impl Debug for State {
 
 impl PartialEq for State {
     fn eq(&self, other: &Self) -> bool {
-        self.variables.iter().all(|(key, value)| {
+        let impls = |state: &State| state.impls.iter()
+            .map(|(key, value)| (key.clone(), value.iter().map(|i| i.to_token_stream().to_string()).collect()))
+            .collect::<BTreeMap<String, Vec<String>>>();
+        impls(self) == impls(other) && self.variables.iter().all(|(key, value)| {
             let other_value = other.variables.get(key);
             match other_value {
                 None => false,
@@ -118,8 +379,4 @@ impl PartialEq for State {
             }
         })
     }
-
-    fn ne(&self, other: &Self) -> bool {
-        !self.eq(other)
-    }
 }
\ No newline at end of file
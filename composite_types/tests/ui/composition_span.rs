@@ -0,0 +1,12 @@
+use composite_types::compose_type;
+
+compose_type! {
+    struct Node {
+        value: Option<u8>
+    }
+    struct Chain = Required(Node);
+}
+
+struct Chain;
+
+fn main() {}
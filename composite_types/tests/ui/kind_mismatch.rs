@@ -0,0 +1,10 @@
+use composite_types::compose_type;
+
+compose_type! {
+    enum State {
+        Loading
+    }
+    struct StatePatch = Optional(State);
+}
+
+fn main() {}
@@ -1,3 +1,5 @@
+#![allow(dead_code, clippy::items_after_test_module)]
+
 use composite_types::{compose_type};
 
 pub fn add(left: usize, right: usize) -> usize {